@@ -40,6 +40,10 @@ struct Show {
     /// 可选。打印 TopLevel（及子命令） 结构体。比如 `rustdx -p day`。
     #[argh(switch, short = 'p', long = "print-struct")]
     pub print_struct: bool,
+
+    /// 可选。对行情服务器测速，按往返时间从快到慢打印可用的地址。
+    #[argh(switch, short = 'b', long = "best-ip")]
+    pub best_ip: bool,
 }
 
 impl TopLevel {
@@ -56,6 +60,11 @@ impl TopLevel {
                 if help.print_struct {
                     println!("{self:#?}");
                 }
+                if help.best_ip {
+                    for l in rustdx::tcp::ip::best_stock_ips() {
+                        println!("{}\t{:?}", l.addr, l.rtt);
+                    }
+                }
                 Ok(())
             }
         }
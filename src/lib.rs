@@ -12,6 +12,8 @@ use thiserror::Error;
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Tcp(#[from] tcp::Error),
     #[error("invalid value (expected {expected:?}, found {found:?})")]
     Invalid { expected: String, found: String },
     #[error("{0}")]
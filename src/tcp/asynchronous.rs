@@ -0,0 +1,251 @@
+//! 基于 tokio 的异步连接。需要开启 `async` feature 。
+//!
+//! 请求字节和解析逻辑与同步的 [`Tcp`][super::Tcp] 完全相同（即 [`Tdx::send`] 和
+//! [`Tdx::parse`]），只是收发字节的方式不同。
+//!
+//! ```rust,no_run
+//! # async fn run() -> rustdx::tcp::Result<()> {
+//! use rustdx::tcp::{asynchronous::AsyncTcp, stock::Kline};
+//!
+//! let mut tcp = AsyncTcp::new().await?;
+//! let data = tcp.recv_parsed(&mut Kline::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! 每个 [`AsyncTcp`] 同一时间只能处理一个请求；并发请求时，为每个任务建立各自的连接。
+//!
+//! # Cancel safety
+//!
+//! 请求的方法都不是 cancel safe 的：在写入请求或者读取响应的过程中取消 future
+//! （比如外层的 `tokio::time::timeout` 超时，或者在 `tokio::select!` 中被其他分支抢先），
+//! 连接中会残留未读取的响应，之后的请求都会读到错位的响应（[`Error::SeqMismatch`]）。
+//! 这时连接被标记为出错（见 [`AsyncTcp::is_broken`]），应丢弃这个连接并重新连接。
+
+use super::{
+    decompress,
+    ip::{self, STOCK_IP},
+    with_seq, Error, Request, ResponseHeader, Result, Tdx, PACK1, PACK2, PACK3, RECV_SIZE, TIMEOUT,
+};
+use log::trace;
+use std::{
+    future::Future,
+    io::{self, ErrorKind},
+    net::SocketAddr,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+/// 异步 Tcp 连接。
+#[derive(Debug)]
+pub struct AsyncTcp {
+    stream: BufReader<TcpStream>,
+    recv: [u8; RECV_SIZE],
+    /// 上一次请求的序号
+    seq: u32,
+    timeout: Duration,
+    /// 见 [`AsyncTcp::is_broken`]
+    broken: bool,
+}
+
+impl AsyncTcp {
+    /// 与 [`Tcp::new`][super::Tcp::new] 的顺序相同：按照 [`ip::fastest_stock_ips`] 的顺序连接，
+    /// 都失败时连接 `STOCK_IP[0]` ，返回第一个成功的连接。已发送三个测试包。
+    ///
+    /// 第一次调用时在 [`spawn_blocking`][tokio::task::spawn_blocking] 中对所有服务器测速。
+    pub async fn new() -> Result<Self> {
+        let fastest = tokio::task::spawn_blocking(ip::fastest_stock_ips)
+            .await
+            .unwrap_or_default();
+        let mut err = None;
+        let fallback = Some(&STOCK_IP[0]).filter(|ip| fastest.iter().all(|l| l.addr != **ip));
+        for ip in fastest.iter().map(|l| &l.addr).chain(fallback) {
+            match Self::new_with_ip(ip).await {
+                Ok(tcp) => return Ok(tcp),
+                Err(e) => err = Some(e),
+            }
+        }
+        Err(err.unwrap_or(Error::NoServer))
+    }
+
+    /// 已发送三个测试包
+    pub async fn new_with_ip(ip: &SocketAddr) -> Result<Self> {
+        let stream = with_timeout(TIMEOUT, TcpStream::connect(ip))
+            .await
+            .map_err(|source| Error::Connect { addr: *ip, source })?;
+        let mut tcp = Self {
+            stream: BufReader::new(stream),
+            recv: [0; RECV_SIZE],
+            seq: 0,
+            timeout: TIMEOUT,
+            broken: false,
+        };
+        for (pack, tag) in [(PACK1, "PACK1"), (PACK2, "PACK2"), (PACK3, "PACK3")] {
+            tcp.send_recv(pack, tag).await?;
+        }
+        Ok(tcp)
+    }
+
+    /// 修改每次连接、写入和读取的超时时间，默认为 [`TIMEOUT`] 。
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn get_ref_recv(&self) -> &[u8] {
+        &self.recv
+    }
+
+    /// 上一次请求的序号，同 [`Tcp::seq`][super::Tcp::seq] 。
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// 之前是否有请求出错（网络错误或者响应有误），或者在读写的过程中被取消。
+    ///
+    /// 同 [`Tcp::is_broken`][super::Tcp::is_broken] ：连接中可能残留未读取的响应，应重新连接。
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// 同 [`send_recv`][super::send_recv] ：发送写入序号的字节，接收 16 字节的响应信息和之后的
+    /// 有效数据，检查响应与请求对应之后，返回响应信息和有效数据。
+    ///
+    /// 出错或者在完成之前被取消时，连接被标记为出错，见 [`AsyncTcp::is_broken`] 。
+    pub async fn send_recv(&mut self, send: &[u8], tag: &str) -> Result<(ResponseHeader, Vec<u8>)> {
+        let broken = std::mem::replace(&mut self.broken, true);
+        // future 在这里被取消时，broken 保持为 true
+        let res = self.send_recv_frame(send, tag).await;
+        self.broken = broken || res.is_err();
+        res
+    }
+
+    async fn send_recv_frame(
+        &mut self,
+        send: &[u8],
+        tag: &str,
+    ) -> Result<(ResponseHeader, Vec<u8>)> {
+        self.seq = self.seq.wrapping_add(1);
+        let send = &with_seq(send, self.seq)[..];
+        let timeout = self.timeout;
+        with_timeout(timeout, self.stream.get_mut().write_all(send)).await?;
+        let header = self.read(RECV_SIZE).await?;
+        self.recv.copy_from_slice(&header);
+        trace!("{}\nsend: {:?}\nrecv[16B]: {:?}", tag, send, self.recv);
+        let header = ResponseHeader::parse(&self.recv)?;
+
+        let buf = self
+            .read(header.deflate_size as usize)
+            .await
+            .map_err(|e| match e {
+                Error::ShortFrame { expected, found } => Error::ShortFrame {
+                    expected: RECV_SIZE + expected,
+                    found: RECV_SIZE + found,
+                },
+                e => e,
+            })?;
+        header.check(send)?;
+        Ok((header, buf))
+    }
+
+    /// 读取 len 字节。连接在读完之前断开时，得到 [`Error::ShortFrame`] 。
+    async fn read(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(len);
+        let mut stream = (&mut self.stream).take(len as u64);
+        with_timeout(self.timeout, stream.read_to_end(&mut buf)).await?;
+        if buf.len() < len {
+            return Err(Error::ShortFrame {
+                expected: len,
+                found: buf.len(),
+            });
+        }
+        Ok(buf)
+    }
+
+    /// 同 [`send_recv_decompress`][super::send_recv_decompress] 。
+    pub async fn send_recv_decompress(&mut self, send: &[u8], tag: &str) -> Result<Vec<u8>> {
+        let (header, buf) = self.send_recv(send, tag).await?;
+        let res = decompress(buf, &header);
+        self.broken |= res.is_err();
+        res
+    }
+
+    /// 同 [`Tdx::recv`] 。
+    pub async fn recv<T: Tdx>(&mut self, tdx: &mut T) -> Result<Vec<u8>> {
+        self.send_recv_decompress(tdx.send(), T::TAG).await
+    }
+
+    /// 同 [`Tdx::recv_parsed`] ：得到和解析响应的字节，并返回解析的数据。
+    pub async fn recv_parsed<'t, T: Tdx>(&mut self, tdx: &'t mut T) -> Result<&'t T::Item> {
+        let response = self.recv(tdx).await?;
+        tdx.parse(response)?;
+        Ok(tdx.result())
+    }
+
+    /// 同 [`Tcp::request`][super::Tcp::request] ：返回 owned 的响应，见 [`Request`] 。
+    pub async fn request<R: Request>(&mut self, req: &R) -> Result<R::Response> {
+        let response = self.send_recv_decompress(&req.send(), R::TAG).await?;
+        req.decode(&response)
+    }
+}
+
+/// 超时时得到 [`ErrorKind::TimedOut`] 错误。
+async fn with_timeout<T>(
+    timeout: Duration,
+    fut: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    tokio::time::timeout(timeout, fut)
+        .await
+        .map_err(|_| io::Error::new(ErrorKind::TimedOut, "请求超时"))?
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn unreachable_server() {
+    // 保留地址（RFC 5737），不会有服务器响应
+    let ip = "192.0.2.1:7709".parse().unwrap();
+    assert!(AsyncTcp::new_with_ip(&ip).await.is_err());
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn cancelled_request() -> Result<()> {
+    use super::mock::{respond, MockServer};
+
+    // 延迟 50 毫秒回复 SecurityCount
+    let server = MockServer::start_with(|request| {
+        if crate::bytes_helper::u16_from_le_bytes(request, 10) == 0x044e {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        respond(request)
+    })?;
+    let mut tcp = AsyncTcp::new_with_ip(&server.addr()).await?;
+    let mut count = super::SecurityCount::new(super::Market::SZ);
+    assert_eq!(*tcp.recv_parsed(&mut count).await?, 13471);
+    assert!(!tcp.is_broken());
+    // 写入请求之后、读取响应之前取消
+    let cancelled = tokio::time::timeout(Duration::from_millis(10), tcp.recv(&mut count)).await;
+    assert!(cancelled.is_err());
+    assert!(tcp.is_broken());
+    // 残留的响应使之后的请求错位，且连接保持出错的状态
+    assert!(tcp.recv(&mut count).await.is_err());
+    assert!(tcp.is_broken());
+    Ok(())
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn connection() -> Result<()> {
+    let mut tcp = AsyncTcp::new().await?;
+    let mut kline = super::stock::Kline::default();
+    assert_eq!(tcp.recv_parsed(&mut kline).await?.len(), 3);
+    assert!(
+        *tcp.recv_parsed(&mut super::SecurityCount::new(super::Market::SZ))
+            .await?
+            > 0
+    );
+    Ok(())
+}
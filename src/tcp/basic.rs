@@ -1,4 +1,5 @@
-use super::{u16_from_le_bytes, Result, Tdx};
+use super::{ensure_len, Market, Request, Result, Tdx};
+use crate::bytes_helper::u16_from_le_bytes;
 
 /// 心跳包。用于保持 Tcp 连接。
 pub type Heartbeat = SecurityCount;
@@ -8,30 +9,28 @@ pub type Heartbeat = SecurityCount;
 #[derive(Debug)]
 pub struct SecurityCount {
     send: Box<[u8]>,
-    /// 0 代表深市；1 代表沪市。
-    market: u16,
+    market: Market,
     /// 响应的结果：证券数量
     count: u16,
 }
 
 impl SecurityCount {
-    /// market = 0 或 1，表示深市或沪市。
-    pub fn new(market: u16) -> Self {
-        let mut send = [0; Self::LEN];
-        send.copy_from_slice(Self::SEND);
-        if market != 0 {
-            send[12..14].copy_from_slice(&market.to_le_bytes());
-        }
+    pub fn new(market: Market) -> Self {
         Self {
-            send: send.into(),
+            send: SecurityCountRequest::new(market).send(),
             market,
             count: 0,
         }
     }
 
-    pub fn market(&mut self, market: u16) {
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> SecurityCountRequest {
+        SecurityCountRequest::new(self.market)
+    }
+
+    pub fn market(&mut self, market: Market) {
         self.market = market;
-        self.send[12..14].copy_from_slice(&market.to_le_bytes());
+        self.send[12..14].copy_from_slice(&market.code().to_le_bytes());
     }
 }
 
@@ -45,12 +44,13 @@ impl Tdx for SecurityCount {
     ];
     const TAG: &'static str = "heartbeat";
 
-    fn send(&mut self) -> &[u8] {
+    fn send(&self) -> &[u8] {
         &self.send
     }
 
-    fn parse(&mut self, response: Vec<u8>) {
-        self.count = u16_from_le_bytes(&response, 0);
+    fn parse(&mut self, response: Vec<u8>) -> Result<()> {
+        self.count = self.request().decode(&response)?;
+        Ok(())
     }
 
     fn result(&self) -> &Self::Item {
@@ -58,6 +58,35 @@ impl Tdx for SecurityCount {
     }
 }
 
+/// [`SecurityCount`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityCountRequest {
+    pub market: Market,
+}
+
+impl SecurityCountRequest {
+    pub fn new(market: Market) -> Self {
+        Self { market }
+    }
+}
+
+impl Request for SecurityCountRequest {
+    const TAG: &'static str = <SecurityCount as Tdx>::TAG;
+    type Response = u16;
+
+    fn send(&self) -> Box<[u8]> {
+        let mut send = [0; SecurityCount::LEN];
+        send.copy_from_slice(SecurityCount::SEND);
+        send[12..14].copy_from_slice(&self.market.code().to_le_bytes());
+        send.into()
+    }
+
+    fn decode(&self, response: &[u8]) -> Result<u16> {
+        ensure_len(Self::TAG, response, 2)?;
+        Ok(u16_from_le_bytes(response, 0))
+    }
+}
+
 /// 查询证券列表。对应于 pytdx 中的 GetSecurityList。
 ///
 /// ## 注意：
@@ -67,7 +96,7 @@ impl Tdx for SecurityCount {
 #[derive(Debug, Clone)]
 pub struct SecurityList {
     pub send: Box<[u8]>,
-    pub market: u16,
+    pub market: Market,
     pub start: u16,
     /// 响应信息中的列表长度。
     pub count: usize,
@@ -83,7 +112,7 @@ impl Default for SecurityList {
                 arr.copy_from_slice(Self::SEND);
                 arr.into()
             },
-            market: 0,
+            market: Market::SZ,
             start: 1,
             count: 0,
             response: Vec::new(),
@@ -94,18 +123,12 @@ impl Default for SecurityList {
 
 impl SecurityList {
     /// 参数说明：
-    /// - market = 0 或 1，表示深市或沪市；
-    /// - start 在 [0, n] 的范围内，其中 n 是 [`SecurityCount`] 得到的结果。 目前 market = 0
-    ///   时，有 13471 条； market = 1 时，有 18065 条。
-    pub fn new(market: u16, start: u16) -> Self {
+    /// - market 为深市或沪市；
+    /// - start 在 [0, n] 的范围内，其中 n 是 [`SecurityCount`] 得到的结果。 目前深市有 13471
+    ///   条；沪市有 18065 条。
+    pub fn new(market: Market, start: u16) -> Self {
         Self {
-            send: {
-                let mut arr = [0; Self::LEN];
-                arr.copy_from_slice(Self::SEND);
-                arr[12..14].copy_from_slice(&market.to_le_bytes());
-                arr[14..16].copy_from_slice(&start.to_le_bytes());
-                arr.into()
-            },
+            send: SecurityListRequest::new(market, start).send(),
             market,
             start,
             count: 0,
@@ -113,6 +136,11 @@ impl SecurityList {
             data: [].into(),
         }
     }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> SecurityListRequest {
+        SecurityListRequest::new(self.market, self.start)
+    }
 }
 
 impl Tdx for SecurityList {
@@ -125,19 +153,15 @@ impl Tdx for SecurityList {
     ];
     const TAG: &'static str = "股票、指数列表";
 
-    fn send(&mut self) -> &[u8] {
+    fn send(&self) -> &[u8] {
         &self.send
     }
 
-    /// 前 2 字节表示列表的长度，剩余字节中，每 29 字节使用 [`SecurityListData::parse`] 解析。
-    fn parse(&mut self, v: Vec<u8>) {
-        self.count = u16_from_le_bytes(&v, 0) as usize;
-        self.data = v[2..]
-            .chunks_exact(29)
-            .map(SecurityListData::parse)
-            .collect();
-        debug_assert_eq!(self.count, self.data.len());
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?.into();
+        self.count = self.data.len();
         self.response = v;
+        Ok(())
     }
 
     fn result(&self) -> &Self::Item {
@@ -145,12 +169,60 @@ impl Tdx for SecurityList {
     }
 }
 
+/// [`SecurityList`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityListRequest {
+    pub market: Market,
+    pub start: u16,
+}
+
+impl SecurityListRequest {
+    /// 参数同 [`SecurityList::new`] 。
+    pub fn new(market: Market, start: u16) -> Self {
+        Self { market, start }
+    }
+}
+
+impl Request for SecurityListRequest {
+    const TAG: &'static str = <SecurityList as Tdx>::TAG;
+    type Response = Vec<SecurityListData>;
+
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; SecurityList::LEN];
+        arr.copy_from_slice(SecurityList::SEND);
+        arr[12..14].copy_from_slice(&self.market.code().to_le_bytes());
+        arr[14..16].copy_from_slice(&self.start.to_le_bytes());
+        arr.into()
+    }
+
+    /// 前 2 字节表示列表的长度，剩余字节中，每 29 字节使用 [`SecurityListData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 2)?;
+        let count = u16_from_le_bytes(v, 0) as usize;
+        ensure_len(Self::TAG, v, 2 + count * 29)?;
+        Ok(v[2..]
+            .chunks_exact(29)
+            .take(count)
+            .map(SecurityListData::parse)
+            .collect())
+    }
+}
+
 #[test]
 fn connection() -> Result<()> {
     SecurityList::default().recv_parsed(&mut crate::tcp::Tcp::new()?)?;
     Ok(())
 }
 
+#[test]
+fn request() {
+    let mut count = SecurityCount::new(Market::SZ);
+    count.market(Market::SH);
+    crate::tcp::tests::same_request(&count, &count.request());
+    let list = SecurityList::new(Market::SH, 1000);
+    crate::tcp::tests::same_request(&list, &list.request());
+}
+
 /// [`SecurityList`] 的解析结果。具体为指数、股票、债券等证券的代码、名称。
 ///
 /// ## 注意
@@ -176,7 +248,7 @@ impl SecurityListData {
     /// ) = struct.unpack("<6sH8s4sBI4s", bytes) # python 表示方式
     /// ```
     pub fn parse(bytes: &[u8]) -> Self {
-        let code = unsafe { std::str::from_utf8_unchecked(&bytes[0..6]) }.into();
+        let code = String::from_utf8_lossy(&bytes[0..6]).into();
         let (name, encoding_used, had_errors) = encoding_rs::GBK.decode(&bytes[8..16]);
         debug_assert_eq!(encoding_used, encoding_rs::GBK);
         debug_assert!(!had_errors);
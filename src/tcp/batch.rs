@@ -0,0 +1,196 @@
+//! 流水线：在一个连接上连续发送多个请求，再依次读取响应。
+//!
+//! 逐个请求时，每次 [`Tdx::recv_parsed`] 都要等待一次往返；对大量证券请求 K 线时，
+//! 往返的时间远多于服务器处理的时间。流水线每次连续发送至多 [`BATCH_SIZE`] 个请求，
+//! 然后读取这些请求的响应，并根据响应信息中的序号找到对应的请求。
+
+use super::{
+    decompress, read_frame, request_seq, Error, Request, ResponseHeader, Result, Tcp, Tdx,
+};
+use std::io::Write;
+
+/// 流水线每次连续发送的请求数量。
+///
+/// 发送之后才开始读取响应，所以一次发送过多请求时，未读取的响应会积压在服务器和本地的缓冲区中。
+pub const BATCH_SIZE: usize = 64;
+
+impl Tcp {
+    /// 同 [`send_recv`][super::send_recv] ，但以流水线的方式发送多个请求，
+    /// 按照请求的顺序返回响应信息和未解压的有效数据。
+    ///
+    /// 响应的序号不属于任何未得到响应的请求时，返回 [`Error::SeqMismatch`] 。
+    /// 任何一个请求出错时，返回错误，此时连接中可能残留其他请求的响应，应重新连接
+    /// （见 [`Tcp::is_broken`]）。
+    pub fn send_recv_batch(
+        &mut self,
+        sends: &[&[u8]],
+        tag: &str,
+    ) -> Result<Vec<(ResponseHeader, Vec<u8>)>> {
+        let res = self.send_recv_pipelined(sends, tag);
+        self.track(res)
+    }
+
+    fn send_recv_pipelined(
+        &mut self,
+        sends: &[&[u8]],
+        tag: &str,
+    ) -> Result<Vec<(ResponseHeader, Vec<u8>)>> {
+        let mut frames = Vec::with_capacity(sends.len());
+        for chunk in sends.chunks(BATCH_SIZE) {
+            let requests: Vec<_> = chunk.iter().map(|send| self.next_request(send)).collect();
+            self.stream.write_all(&requests.concat())?;
+
+            let mut responses: Vec<Option<_>> = (0..requests.len()).map(|_| None).collect();
+            for _ in 0..requests.len() {
+                let (header, buf) = read_frame(self, 0, tag)?;
+                let pos = requests
+                    .iter()
+                    .zip(&responses)
+                    .position(|(r, resp)| resp.is_none() && request_seq(r) == header.seq)
+                    .ok_or_else(|| Error::SeqMismatch {
+                        // 最早的未得到响应的请求
+                        expected: requests
+                            .iter()
+                            .zip(&responses)
+                            .find_map(|(r, resp)| resp.is_none().then(|| request_seq(r)))
+                            .unwrap_or_default(),
+                        found: header.seq,
+                    })?;
+                self.write_record(&requests[pos], &buf)?;
+                header.check(&requests[pos])?;
+                responses[pos] = Some((header, buf));
+            }
+            frames.extend(responses.into_iter().flatten());
+        }
+        Ok(frames)
+    }
+
+    /// 同 [`Tdx::recv`] ：以流水线的方式得到每个请求的响应的字节（已解压），顺序与 tdxs 相同。
+    pub fn recv_batch<T: Tdx>(&mut self, tdxs: &mut [T]) -> Result<Vec<Vec<u8>>> {
+        let sends: Vec<_> = tdxs.iter().map(Tdx::send).collect();
+        self.send_recv_batch_decompress(&sends, T::TAG)
+    }
+
+    fn send_recv_batch_decompress(&mut self, sends: &[&[u8]], tag: &str) -> Result<Vec<Vec<u8>>> {
+        let res = self
+            .send_recv_batch(sends, tag)?
+            .into_iter()
+            .map(|(header, buf)| decompress(buf, &header))
+            .collect();
+        self.track(res)
+    }
+
+    /// 同 [`Tdx::recv_parsed`] ：以流水线的方式得到和解析每个请求的响应。
+    /// 解析的数据通过每个请求的 [`Tdx::result`] 得到。
+    ///
+    /// ```rust,no_run
+    /// use rustdx::tcp::{stock::Kline, KlineCategory, Market, Tcp, Tdx};
+    ///
+    /// let mut tcp = Tcp::new()?;
+    /// let codes = ["000001", "000002", "399001"];
+    /// let mut klines: Vec<_> = codes
+    ///     .iter()
+    ///     .map(|c| Kline::new(Market::SZ, c, KlineCategory::Day, 0, 800))
+    ///     .collect();
+    /// tcp.recv_parsed_batch(&mut klines)?;
+    /// for kline in &klines {
+    ///     println!("{:?}", kline.result().last());
+    /// }
+    /// # Ok::<(), rustdx::tcp::Error>(())
+    /// ```
+    pub fn recv_parsed_batch<T: Tdx>(&mut self, tdxs: &mut [T]) -> Result<()> {
+        let responses = self.recv_batch(tdxs)?;
+        for (tdx, response) in tdxs.iter_mut().zip(responses) {
+            tdx.parse(response)?;
+        }
+        Ok(())
+    }
+
+    /// 同 [`Tcp::request`] ：以流水线的方式发送多个请求，按照请求的顺序返回解析后的响应。
+    pub fn request_batch<R: Request>(&mut self, reqs: &[R]) -> Result<Vec<R::Response>> {
+        let sends: Vec<_> = reqs.iter().map(Request::send).collect();
+        let sends: Vec<_> = sends.iter().map(|send| &**send).collect();
+        self.send_recv_batch_decompress(&sends, R::TAG)?
+            .iter()
+            .zip(reqs)
+            .map(|(response, req)| req.decode(response))
+            .collect()
+    }
+}
+
+#[test]
+fn pipelined() -> Result<()> {
+    use super::{mock::MockServer, stock::Kline, KlineCategory::Day, Market, SecurityCount};
+
+    let server = MockServer::start()?;
+    let mut tcp = server.connect()?;
+    let mut klines: Vec<_> = (0..5)
+        .map(|i| Kline::new(Market::SZ, "000001", Day, i, 1))
+        .collect();
+    tcp.recv_parsed_batch(&mut klines)?;
+    for (i, kline) in klines.iter().enumerate() {
+        let mut single = Kline::new(Market::SZ, "000001", Day, i as u16, 1);
+        let single = single.recv_parsed(&mut tcp)?;
+        assert_eq!(format!("{:?}", kline.result()), format!("{single:?}"));
+    }
+
+    // 超过 BATCH_SIZE 时分多次发送
+    let mut counts: Vec<_> = (0..BATCH_SIZE * 2 + 1)
+        .map(|i| SecurityCount::new([Market::SZ, Market::SH][i % 2]))
+        .collect();
+    let seq = tcp.seq();
+    tcp.recv_parsed_batch(&mut counts)?;
+    assert_eq!(tcp.seq(), seq + counts.len() as u32);
+    assert!(counts
+        .iter()
+        .enumerate()
+        .all(|(i, c)| *c.result() == [13471, 18065][i % 2]));
+    assert!(tcp.recv_parsed_batch(&mut [] as &mut [Kline]).is_ok());
+
+    let reqs: Vec<_> = counts[..3].iter().map(SecurityCount::request).collect();
+    let responses = tcp.request_batch(&reqs)?;
+    assert_eq!(responses, [13471, 18065, 13471]);
+    Ok(())
+}
+
+#[test]
+fn out_of_order() -> Result<()> {
+    use super::{mock::respond, Market, SecurityCount};
+    use std::io::Read;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    // 按相反的顺序响应两个请求；之后重复响应第一个请求
+    let server = std::thread::spawn(move || -> std::io::Result<()> {
+        let (mut stream, _) = listener.accept()?;
+        let mut requests = [[0; SecurityCount::SEND.len()]; 2];
+        for request in &mut requests {
+            stream.read_exact(request)?;
+        }
+        for request in requests.iter().rev() {
+            stream.write_all(&respond(request))?;
+        }
+        for request in &mut requests {
+            stream.read_exact(request)?;
+        }
+        stream.write_all(&respond(&requests[0]).repeat(2))
+    });
+
+    let mut tcp = Tcp::builder().packs(false).connect(&addr)?;
+    let mut counts = [
+        SecurityCount::new(Market::SZ),
+        SecurityCount::new(Market::SH),
+    ];
+    tcp.recv_parsed_batch(&mut counts)?;
+    assert_eq!((*counts[0].result(), *counts[1].result()), (13471, 18065));
+    let err = tcp.recv_parsed_batch(&mut counts).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::SeqMismatch {
+            expected: 4,
+            found: 3
+        }
+    ));
+    server.join().unwrap()?;
+    Ok(())
+}
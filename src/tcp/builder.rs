@@ -0,0 +1,183 @@
+use super::{send_packs, BufTcp, Error, Result, Tcp, RECV_SIZE, TIMEOUT};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::{
+    io::BufReader,
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+/// 建立 [`Tcp`] 连接的选项。
+///
+/// 默认值与 [`Tcp::new_with_ip`] 相同：连接、读取、写入的超时都为 [`TIMEOUT`] ，
+/// 不设置 TCP_NODELAY ，不绑定本地地址，连接之后发送三个测试包。
+///
+/// ```rust,no_run
+/// use rustdx::tcp::{ip::STOCK_IP, TcpBuilder};
+/// use std::time::Duration;
+///
+/// let tcp = TcpBuilder::new()
+///     .connect_timeout(Duration::from_secs(1))
+///     .read_timeout(Some(Duration::from_secs(3)))
+///     .nodelay(true)
+///     .connect(&STOCK_IP[0])?;
+/// # Ok::<(), rustdx::tcp::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpBuilder {
+    pub connect_timeout: Duration,
+    /// None 表示读取时一直阻塞
+    pub read_timeout: Option<Duration>,
+    /// None 表示写入时一直阻塞
+    pub write_timeout: Option<Duration>,
+    pub nodelay: bool,
+    /// 连接之前绑定的本地地址
+    pub bind: Option<SocketAddr>,
+    /// 连接之后是否发送三个测试包
+    pub packs: bool,
+}
+
+impl Default for TcpBuilder {
+    fn default() -> Self {
+        Self {
+            connect_timeout: TIMEOUT,
+            read_timeout: Some(TIMEOUT),
+            write_timeout: Some(TIMEOUT),
+            nodelay: false,
+            bind: None,
+            packs: true,
+        }
+    }
+}
+
+impl TcpBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 修改连接的超时时间。
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// 修改读取的超时时间。
+    ///
+    /// ## 注意
+    /// `Some(Duration::ZERO)` 是无效的值，连接时会得到错误。
+    pub fn read_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// 修改写入的超时时间。
+    ///
+    /// ## 注意
+    /// `Some(Duration::ZERO)` 是无效的值，连接时会得到错误。
+    pub fn write_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.write_timeout = timeout;
+        self
+    }
+
+    /// 是否设置 TCP_NODELAY 。
+    pub fn nodelay(&mut self, nodelay: bool) -> &mut Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// 连接之前绑定本地地址（比如有多个网卡时）。
+    pub fn bind(&mut self, addr: SocketAddr) -> &mut Self {
+        self.bind = Some(addr);
+        self
+    }
+
+    /// 连接之后是否发送三个测试包。不发送时，需要自行握手（比如扩展行情）。
+    pub fn packs(&mut self, packs: bool) -> &mut Self {
+        self.packs = packs;
+        self
+    }
+
+    /// 按照选项建立连接。
+    pub fn connect(&self, ip: &SocketAddr) -> Result<Tcp> {
+        let (stream, buffer, recv) = self.tcpstream(ip)?;
+        let mut tcp = Tcp {
+            stream,
+            buffer,
+            recv,
+            seq: 0,
+            record: None,
+            broken: false,
+        };
+        if self.packs {
+            send_packs(&mut tcp, false)?;
+        }
+        Ok(tcp)
+    }
+
+    /// 按照选项建立 tcpstream ，不发送测试包。连接失败时返回 [`Error::Connect`] 。
+    pub fn tcpstream(&self, ip: &SocketAddr) -> Result<(TcpStream, BufTcp, [u8; RECV_SIZE])> {
+        let socket = Socket::new(Domain::for_address(*ip), Type::STREAM, Some(Protocol::TCP))?;
+        if let Some(bind) = &self.bind {
+            socket.bind(&(*bind).into())?;
+        }
+        socket
+            .connect_timeout(&(*ip).into(), self.connect_timeout)
+            .map_err(|source| Error::Connect { addr: *ip, source })?;
+        let stream = TcpStream::from(socket);
+        stream.set_read_timeout(self.read_timeout)?;
+        stream.set_write_timeout(self.write_timeout)?;
+        stream.set_nodelay(self.nodelay)?;
+        let buffer = BufReader::new(stream.try_clone()?);
+        Ok((stream, buffer, [0; RECV_SIZE]))
+    }
+}
+
+#[test]
+fn new_modify() {
+    let mut builder = TcpBuilder::new();
+    builder
+        .connect_timeout(Duration::from_secs(1))
+        .read_timeout(None)
+        .write_timeout(Some(Duration::from_secs(2)))
+        .nodelay(true)
+        .bind("0.0.0.0:0".parse().unwrap())
+        .packs(false);
+    assert_ne!(builder, TcpBuilder::default());
+    builder
+        .connect_timeout(TIMEOUT)
+        .read_timeout(Some(TIMEOUT))
+        .write_timeout(Some(TIMEOUT))
+        .nodelay(false)
+        .packs(true)
+        .bind = None;
+    assert_eq!(builder, TcpBuilder::default());
+}
+
+#[test]
+fn local_server() -> Result<()> {
+    // 本地监听，只检查连接的选项，不发送测试包
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let tcp = TcpBuilder::new()
+        .read_timeout(Some(Duration::from_secs(3)))
+        .write_timeout(None)
+        .nodelay(true)
+        .bind("127.0.0.1:0".parse().unwrap())
+        .packs(false)
+        .connect(&listener.local_addr()?)?;
+    let (stream, ..) = tcp.get_ref();
+    assert_eq!(stream.read_timeout()?, Some(Duration::from_secs(3)));
+    assert_eq!(stream.write_timeout()?, None);
+    assert!(stream.nodelay()?);
+    assert_eq!(stream.peer_addr()?, listener.local_addr()?);
+    Ok(())
+}
+
+#[test]
+fn connection() -> Result<()> {
+    let mut tcp = TcpBuilder::new()
+        .nodelay(true)
+        .connect(&super::ip::STOCK_IP[0])?;
+    assert!(
+        *super::Tdx::recv_parsed(&mut super::SecurityCount::new(super::Market::SZ), &mut tcp)? > 0
+    );
+    Ok(())
+}
@@ -0,0 +1,341 @@
+//! 可自动重连和重试的客户端。
+//!
+//! 使用 [`Tcp`] 时，一次读取超时（默认 [`TIMEOUT`][super::TIMEOUT] 为 100 毫秒）就会得到
+//! [`Error::Timeout`] 错误，而且缓冲区中可能残留着未读完的响应字节，之后的请求都会错位，所以这个
+//! [`Tcp`] 已经不能再使用了。
+//!
+//! [`Client`] 在请求失败或者发现连接错位时，丢弃当前连接，重新连接并发送测试包，
+//! 然后按指数退避的间隔重试。所有的 [`Tdx`] 请求都是查询，可以安全地重试。
+//! 只重试网络错误和服务器返回的字节有误（见 [`Error::is_retryable`]），解析失败的错误直接返回。
+//!
+//! ```rust,no_run
+//! use rustdx::tcp::{client::Client, stock::Kline};
+//!
+//! let mut client = Client::new();
+//! client.retries(5);
+//! let data = client.recv_parsed(&mut Kline::default())?;
+//! # Ok::<(), rustdx::tcp::client::RetryError>(())
+//! ```
+
+use super::{Error, Heartbeat, Market, Request, Tcp, Tdx};
+use log::{debug, warn};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// 重试之后仍然失败的错误。
+#[derive(Debug, thiserror::Error)]
+#[error("{tag}：尝试 {attempts} 次均失败，最后一次的错误：{source}")]
+pub struct RetryError {
+    /// 请求的用途，即 [`Tdx::TAG`]
+    pub tag: &'static str,
+    /// 尝试的次数（包括第一次请求）
+    pub attempts: u32,
+    /// 最后一次的错误
+    #[source]
+    pub source: Error,
+}
+
+/// 可自动重连和重试的客户端。见 [模块文档][self] 。
+#[derive(Debug)]
+pub struct Client {
+    /// None 表示需要重新连接
+    tcp: Option<Tcp>,
+    /// 固定的服务器地址。None 表示使用 [`Tcp::new`] 连接最快的服务器。
+    addr: Option<SocketAddr>,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// 默认重试的次数。
+    pub const RETRIES: u32 = 3;
+    /// 默认第一次重试之前等待的时间，之后每次翻倍。
+    pub const BACKOFF: Duration = Duration::from_millis(50);
+
+    /// 连接最快的服务器（见 [`Tcp::new`]）。第一次请求时才建立连接。
+    pub fn new() -> Self {
+        Self {
+            tcp: None,
+            addr: None,
+            retries: Self::RETRIES,
+            backoff: Self::BACKOFF,
+        }
+    }
+
+    /// 连接固定的服务器。第一次请求时才建立连接。
+    pub fn with_ip(addr: SocketAddr) -> Self {
+        Self {
+            addr: Some(addr),
+            ..Self::new()
+        }
+    }
+
+    /// 修改重试的次数。为 0 表示不重试，但仍会在连接错位时重新连接。
+    pub fn retries(&mut self, retries: u32) -> &mut Self {
+        self.retries = retries;
+        self
+    }
+
+    /// 修改第一次重试之前等待的时间，之后每次重试的等待时间翻倍。
+    pub fn backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// 当前的连接。连接已断开或者错位时，重新连接并发送测试包。
+    pub fn tcp(&mut self) -> super::Result<&mut Tcp> {
+        // 缓冲区中残留着上一次未读完的字节时，之后的响应都会错位
+        if self
+            .tcp
+            .as_ref()
+            .is_some_and(|tcp| !tcp.buffer.buffer().is_empty())
+        {
+            warn!("连接错位，重新连接");
+            self.tcp = None;
+        }
+        match &mut self.tcp {
+            Some(tcp) => Ok(tcp),
+            tcp @ None => {
+                let new = match &self.addr {
+                    Some(addr) => Tcp::new_with_ip(addr)?,
+                    None => Tcp::new()?,
+                };
+                Ok(tcp.insert(new))
+            }
+        }
+    }
+
+    /// 丢弃当前连接，下次请求时重新连接。
+    pub fn disconnect(&mut self) {
+        self.tcp = None;
+    }
+
+    /// 得到和解析响应的字节，并返回解析的数据。
+    ///
+    /// 请求失败时，丢弃当前连接，等待之后重新连接并重试，最多重试 retries 次。
+    /// 解析失败（[`Error::Parse`]）时不重试，直接返回错误，且保留当前连接。
+    pub fn recv_parsed<'t, T: Tdx>(&mut self, tdx: &'t mut T) -> Result<&'t T::Item, RetryError> {
+        self.retry(T::TAG, |tcp| {
+            let response = tdx.recv(tcp)?;
+            tdx.parse(response)
+        })?;
+        Ok(tdx.result())
+    }
+
+    /// 同 [`Tcp::request`] ，失败时按 [`Client::recv_parsed`] 的方式重试。
+    pub fn request<R: Request>(&mut self, req: &R) -> Result<R::Response, RetryError> {
+        self.retry(R::TAG, |tcp| tcp.request(req))
+    }
+
+    /// 在当前连接上执行 f ，失败时丢弃当前连接，等待之后重新连接并重试。
+    /// 不可重试的错误（见 [`Error::is_retryable`]）直接返回。
+    fn retry<O>(
+        &mut self,
+        tag: &'static str,
+        mut f: impl FnMut(&mut Tcp) -> super::Result<O>,
+    ) -> Result<O, RetryError> {
+        let mut wait = self.backoff;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let err = match self.tcp().and_then(&mut f) {
+                Ok(output) => return Ok(output),
+                Err(err) => err,
+            };
+            let retryable = err.is_retryable();
+            if retryable {
+                self.disconnect();
+            }
+            if !retryable || attempts > self.retries {
+                return Err(RetryError {
+                    tag,
+                    attempts,
+                    source: err,
+                });
+            }
+            warn!("{tag}：第 {attempts} 次请求失败（{err}），{wait:?} 后重试");
+            std::thread::sleep(wait);
+            wait = wait.saturating_mul(2);
+        }
+    }
+
+    /// 在后台线程中保持连接，见 [`KeepAlive`] 。
+    pub fn keep_alive(self, interval: Duration) -> KeepAlive {
+        KeepAlive::new(self, interval)
+    }
+}
+
+/// 在后台定时发送心跳包（[`Heartbeat`]）的 [`Client`]，防止空闲的连接被服务器断开。
+///
+/// - 只有在连接空闲超过 interval 时才发送心跳包；
+/// - 心跳包和请求共用一把锁，所以二者不会交错发送；
+/// - 心跳失败（比如服务器已经关闭连接）时，丢弃当前连接，下次请求时重新连接；
+/// - 没有连接时不发送心跳包，也不会主动建立连接。
+///
+/// drop 时停止后台线程。
+#[derive(Debug)]
+pub struct KeepAlive {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// 用于通知后台线程停止
+    stop: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    client: Client,
+    /// 最近一次请求或心跳的时间
+    last_used: Instant,
+    stopped: bool,
+}
+
+impl KeepAlive {
+    pub fn new(client: Client, interval: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                client,
+                last_used: Instant::now(),
+                stopped: false,
+            }),
+            stop: Condvar::new(),
+        });
+        let handle = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || shared.heartbeat(interval))
+        };
+        Self {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    /// 同 [`Client::recv_parsed`] 。请求期间后台线程不会发送心跳包。
+    pub fn recv_parsed<'t, T: Tdx>(&self, tdx: &'t mut T) -> Result<&'t T::Item, RetryError> {
+        let mut state = self.shared.lock();
+        let res = state.client.recv_parsed(tdx);
+        state.last_used = Instant::now();
+        res
+    }
+
+    /// 同 [`Client::request`] 。请求期间后台线程不会发送心跳包。
+    pub fn request<R: Request>(&self, req: &R) -> Result<R::Response, RetryError> {
+        self.with(|client| client.request(req))
+    }
+
+    /// 使用内部的 [`Client`] ，比如连续发送多个请求。期间后台线程不会发送心跳包。
+    pub fn with<R>(&self, f: impl FnOnce(&mut Client) -> R) -> R {
+        let mut state = self.shared.lock();
+        let res = f(&mut state.client);
+        state.last_used = Instant::now();
+        res
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 后台线程：等到连接空闲 interval 之后发送心跳包，直到停止。
+    fn heartbeat(&self, interval: Duration) {
+        let mut state = self.lock();
+        while !state.stopped {
+            let idle = state.last_used.elapsed();
+            if idle < interval {
+                state = self
+                    .stop
+                    .wait_timeout(state, interval - idle)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0;
+                continue;
+            }
+            if let Some(tcp) = state.client.tcp.as_mut() {
+                if let Err(err) = Heartbeat::new(Market::SZ).recv(tcp) {
+                    debug!("心跳失败（{err}），丢弃当前连接");
+                    state.client.disconnect();
+                }
+            }
+            state.last_used = Instant::now();
+        }
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.shared.lock().stopped = true;
+        self.shared.stop.notify_all();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[test]
+fn unreachable_server() {
+    // 保留地址（RFC 5737），不会有服务器响应
+    let mut client = Client::with_ip("192.0.2.1:7709".parse().unwrap());
+    client.retries(2).backoff(Duration::from_millis(1));
+    let err = client
+        .recv_parsed(&mut super::SecurityCount::new(super::Market::SZ))
+        .unwrap_err();
+    assert_eq!(err.attempts, 3);
+    assert_eq!(err.tag, "heartbeat");
+}
+
+#[test]
+fn parse_error_not_retried() -> Result<(), Box<dyn std::error::Error>> {
+    // 模拟服务器对财务信息返回空的有效数据，解析失败
+    let server = super::mock::MockServer::start()?;
+    let mut client = Client::with_ip(server.addr());
+    client.backoff(Duration::from_secs(60));
+    let err = client
+        .request(&super::stock::FinanceInfoRequest::new(Market::SZ, "000001"))
+        .unwrap_err();
+    assert_eq!(err.attempts, 1);
+    assert!(matches!(err.source, Error::Parse { .. }));
+    // 连接仍然可用
+    assert!(client.tcp.is_some());
+    assert!(*client.recv_parsed(&mut super::SecurityCount::new(Market::SZ))? > 0);
+    Ok(())
+}
+
+#[test]
+fn keep_alive_stops() {
+    let start = Instant::now();
+    let keep =
+        Client::with_ip("192.0.2.1:7709".parse().unwrap()).keep_alive(Duration::from_secs(60));
+    // 没有连接时不发送心跳包，也不主动连接
+    assert!(keep.with(|c| c.tcp.is_none()));
+    drop(keep);
+    assert!(start.elapsed() < Duration::from_secs(60));
+}
+
+#[test]
+fn connection() -> Result<(), RetryError> {
+    let mut client = Client::new();
+    assert!(*client.recv_parsed(&mut super::SecurityCount::new(super::Market::SZ))? > 0);
+    // 断开之后，下次请求自动重新连接
+    client.disconnect();
+    assert!(*client.recv_parsed(&mut super::SecurityCount::new(super::Market::SH))? > 0);
+
+    let keep = client.keep_alive(Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(keep.with(|c| c.tcp.is_some()));
+    assert!(*keep.recv_parsed(&mut super::SecurityCount::new(super::Market::SZ))? > 0);
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use std::{io, net::SocketAddr};
+
+/// tcp 模块的错误。
+///
+/// - [`Connect`][Error::Connect]、[`Timeout`][Error::Timeout]、[`Io`][Error::Io]
+///   是网络错误，一般重新连接即可；[`NoServer`][Error::NoServer] 表示所有服务器都连接失败；
+/// - [`ShortFrame`][Error::ShortFrame]、[`BadHeader`][Error::BadHeader]、
+///   [`Decompress`][Error::Decompress]、[`SizeMismatch`][Error::SizeMismatch]
+///   表示服务器返回的字节不完整或者有误；
+/// - [`SeqMismatch`][Error::SeqMismatch]、[`CmdMismatch`][Error::CmdMismatch]
+///   表示响应与请求不对应，连接中的响应已经错位；
+/// - [`Parse`][Error::Parse] 表示有效数据与请求的格式不符，可能是服务器的数据有误，
+///   也可能是请求的字节或者解析的逻辑有误；
+/// - [`NotRecorded`][Error::NotRecorded] 表示回放时没有录制对应的请求。
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("连接 {addr} 失败：{source}")]
+    Connect {
+        addr: SocketAddr,
+        #[source]
+        source: io::Error,
+    },
+    /// 读取或写入超时（[`io::ErrorKind::WouldBlock`] 或 [`io::ErrorKind::TimedOut`]）
+    #[error("请求超时：{0}")]
+    Timeout(#[source] io::Error),
+    #[error(transparent)]
+    Io(io::Error),
+    /// 没有可以连接的服务器（都连接失败，或者都处于冷却期）
+    #[error("没有可用的服务器")]
+    NoServer,
+    /// 连接断开或者录制的字节不完整，没有读取到完整的响应；
+    /// 或者 [`ReportFile`][super::stock::ReportFile] 没有下载到完整的文件
+    #[error("响应不完整（需要 {expected} 字节，只得到 {found} 字节）")]
+    ShortFrame { expected: usize, found: usize },
+    /// 16 字节的响应信息不以 `b1 cb 74 00` 开头
+    #[error("响应信息有误：{0:02x?}")]
+    BadHeader([u8; 16]),
+    #[error("解压失败：{0}")]
+    Decompress(miniz_oxide::inflate::DecompressError),
+    /// 响应信息中的序号与请求的序号不同
+    #[error("响应的序号有误（请求为 {expected}，响应为 {found}）")]
+    SeqMismatch { expected: u32, found: u32 },
+    /// 响应信息中的请求类型与请求的不同
+    #[error("响应的请求类型有误（请求为 {expected:#06x}，响应为 {found:#06x}）")]
+    CmdMismatch { expected: u16, found: u16 },
+    /// 解压后的长度与响应信息中的长度不同
+    #[error("解压后的长度有误（响应信息中为 {expected} 字节，实际为 {found} 字节）")]
+    SizeMismatch { expected: usize, found: usize },
+    #[error("{tag}：解析失败，{reason}")]
+    Parse {
+        /// 请求的用途，即 [`Tdx::TAG`][super::Tdx::TAG]
+        tag: &'static str,
+        reason: String,
+    },
+    /// [`Replay`][super::record::Replay] 中没有录制对应的请求，或者对应的帧都已经回放过
+    #[error("{tag}：没有录制对应的请求 {request:02x?}")]
+    NotRecorded {
+        /// 请求的用途，即 [`Tdx::TAG`][super::Tdx::TAG]
+        tag: &'static str,
+        request: Vec<u8>,
+    },
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Error::Timeout(err),
+            _ => Error::Io(err),
+        }
+    }
+}
+
+impl Error {
+    /// 解析失败的错误。
+    pub fn parse(tag: &'static str, reason: impl Into<String>) -> Self {
+        Error::Parse {
+            tag,
+            reason: reason.into(),
+        }
+    }
+
+    /// 是否为超时错误。
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Timeout(_))
+    }
+
+    /// 是否为服务器返回的字节有误（不完整、响应信息有误、无法解压、长度不符、与请求不对应）。
+    pub fn is_bad_response(&self) -> bool {
+        matches!(
+            self,
+            Error::ShortFrame { .. }
+                | Error::BadHeader(_)
+                | Error::Decompress(_)
+                | Error::SizeMismatch { .. }
+                | Error::SeqMismatch { .. }
+                | Error::CmdMismatch { .. }
+        )
+    }
+
+    /// 是否可以重新连接之后重试：网络错误（连接失败、超时、读写失败、没有可用的服务器）
+    /// 或者服务器返回的字节有误（见 [`Error::is_bad_response`]）。
+    ///
+    /// [`Error::Parse`] 表示响应完整但内容不符合预期，重试也会得到相同的结果。
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Error::Connect { .. } | Error::Timeout(_) | Error::Io(_) | Error::NoServer
+        ) || self.is_bad_response()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 检查有效数据至少有 len 字节，否则返回 [`Error::Parse`] 。
+pub fn ensure_len(tag: &'static str, v: &[u8], len: usize) -> Result<()> {
+    if v.len() < len {
+        Err(Error::parse(
+            tag,
+            format!("有效数据需要至少 {len} 字节，只有 {} 字节", v.len()),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn from_io() {
+    let err = Error::from(io::Error::from(io::ErrorKind::WouldBlock));
+    assert!(err.is_timeout());
+    let err = Error::from(io::Error::from(io::ErrorKind::ConnectionReset));
+    assert!(matches!(err, Error::Io(ref e) if e.kind() == io::ErrorKind::ConnectionReset));
+    assert!(!err.is_bad_response());
+    let err = ensure_len("test", &[0; 3], 4).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "test：解析失败，有效数据需要至少 4 字节，只有 3 字节"
+    );
+}
+
+#[test]
+fn bad_response() -> Result<()> {
+    use super::{send_recv_decompress, Market, ResponseHeader, SecurityCount, Tcp, Tdx};
+    use std::io::{Read, Write};
+
+    let header = |deflate: u16, inflate: u16| {
+        let mut v = vec![0xb1, 0xcb, 0x74, 0x00, 0x0c, 0, 0, 0, 0, 0, 0x4e, 0x04];
+        v.extend(deflate.to_le_bytes());
+        v.extend(inflate.to_le_bytes());
+        v
+    };
+    let zipped = miniz_oxide::deflate::compress_to_vec_zlib(&[1; 8], 6);
+    let responses = vec![
+        [&[0; 16][..], &[0; 2]].concat(),
+        [&header(4, 8)[..], &[0xff; 4]].concat(),
+        [&header(zipped.len() as u16, 4)[..], &zipped].concat(),
+        [&header(4, 4)[..], &[0; 2]].concat(),
+    ];
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = std::thread::spawn(move || -> io::Result<()> {
+        let mut request = [0; SecurityCount::SEND.len()];
+        for mut response in responses {
+            let (mut stream, _) = listener.accept()?;
+            stream.read_exact(&mut request)?;
+            if response[..4] == ResponseHeader::MAGIC {
+                response[5..9].copy_from_slice(&request[1..5]); // 序号
+            }
+            stream.write_all(&response)?;
+        }
+        // 对第一个请求响应两次，第二个请求读到的是第一个请求的响应
+        let (mut stream, _) = listener.accept()?;
+        stream.read_exact(&mut request)?;
+        let response = [&header(2, 2)[..], &[0; 2]].concat();
+        stream.write_all(
+            &[&response[..5], &request[1..5], &response[9..]]
+                .concat()
+                .repeat(2),
+        )?;
+        stream.read_exact(&mut request)
+    });
+
+    let mut errors = Vec::new();
+    for _ in 0..4 {
+        let mut tcp = Tcp::builder().packs(false).connect(&addr)?;
+        errors.push(send_recv_decompress(&mut tcp, SecurityCount::SEND, "test").unwrap_err());
+    }
+    let mut tcp = Tcp::builder().packs(false).connect(&addr)?;
+    assert_eq!(
+        send_recv_decompress(&mut tcp, SecurityCount::SEND, "test")?,
+        [0; 2]
+    );
+    errors.push(send_recv_decompress(&mut tcp, SecurityCount::SEND, "test").unwrap_err());
+    server.join().unwrap()?;
+    assert!(matches!(errors[0], Error::BadHeader(h) if h == [0; 16]));
+    assert!(matches!(errors[1], Error::Decompress(_)));
+    assert!(matches!(
+        errors[2],
+        Error::SizeMismatch {
+            expected: 4,
+            found: 8
+        }
+    ));
+    assert!(matches!(
+        errors[3],
+        Error::ShortFrame {
+            expected: 20,
+            found: 18
+        }
+    ));
+    assert!(matches!(
+        errors[4],
+        Error::SeqMismatch {
+            expected: 2,
+            found: 1
+        }
+    ));
+    assert!(errors.iter().all(Error::is_bad_response));
+
+    let mut count = SecurityCount::new(Market::SZ);
+    assert!(matches!(
+        count.parse(vec![1]),
+        Err(Error::Parse {
+            tag: "heartbeat",
+            ..
+        })
+    ));
+    Ok(())
+}
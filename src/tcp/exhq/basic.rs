@@ -0,0 +1,379 @@
+use crate::{
+    bytes_helper::{u16_from_le_bytes, u32_from_le_bytes},
+    tcp::{ensure_len, helper::gbk, Error, Request, Result, Tdx},
+};
+
+/// 查询扩展行情的市场列表。对应于 pytdx 中的 exhq.get_markets、ExGetMarkets。
+#[derive(Debug, Clone, Default)]
+pub struct ExMarketList {
+    pub response: Vec<u8>,
+    pub data: Vec<ExMarketData>,
+}
+
+impl ExMarketList {
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> ExMarketListRequest {
+        ExMarketListRequest
+    }
+}
+
+impl Tdx for ExMarketList {
+    type Item = [ExMarketData];
+
+    /// 市场列表的请求字节，无查询参数。
+    const SEND: &'static [u8] = &[
+        0x01, 0x02, 0x48, 0x69, 0x00, 0x01, 0x02, 0x00, 0x02, 0x00, 0xf4, 0x23,
+    ];
+    const TAG: &'static str = "扩展行情市场列表";
+
+    fn send(&self) -> &[u8] {
+        Self::SEND
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`ExMarketList`] 的请求，无查询参数。见 [`Request`] 。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExMarketListRequest;
+
+impl Request for ExMarketListRequest {
+    const TAG: &'static str = <ExMarketList as Tdx>::TAG;
+    type Response = Vec<ExMarketData>;
+
+    fn send(&self) -> Box<[u8]> {
+        ExMarketList::SEND.into()
+    }
+
+    /// 前 2 字节表示市场数量，剩余字节中，每 64 字节使用 [`ExMarketData::parse`] 解析。
+    /// category 和 market 都为 0 的条目是无效的，会被忽略。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 2)?;
+        let count = u16_from_le_bytes(v, 0) as usize;
+        ensure_len(Self::TAG, v, 2 + count * 64)?;
+        Ok(v[2..]
+            .chunks_exact(64)
+            .take(count)
+            .map(ExMarketData::parse)
+            .filter(|m| m.category != 0 || m.market != 0)
+            .collect())
+    }
+}
+
+/// [`ExMarketList`] 的解析结果。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ExMarketData {
+    /// 市场代码，查询合约时使用
+    pub market: u8,
+    /// 市场类别
+    pub category: u8,
+    /// 市场名称，比如 “中金所期货”
+    pub name: String,
+    /// 市场简称，比如 “CZ”
+    pub short_name: String,
+}
+
+impl ExMarketData {
+    /// 解析 [`ExMarketList`] 的响应字节。传入长度为 64 字节序列。
+    /// ```python
+    /// (category, name, market, short_name, _, unknown) = struct.unpack("<B32sB2s26s2s", bytes)
+    /// ```
+    /// 其中 name 和 short_name 为 GBK 编码。
+    pub fn parse(bytes: &[u8]) -> Self {
+        Self {
+            category: bytes[0],
+            name: gbk(&bytes[1..33]),
+            market: bytes[33],
+            short_name: gbk(&bytes[34..36]),
+        }
+    }
+}
+
+/// 查询扩展行情的合约数量。对应于 pytdx 中的 exhq.get_instrument_count、ExGetInstrumentCount。
+#[derive(Debug, Clone, Default)]
+pub struct ExInstrumentCount {
+    /// 响应的结果：合约数量
+    pub count: u32,
+}
+
+impl ExInstrumentCount {
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> ExInstrumentCountRequest {
+        ExInstrumentCountRequest
+    }
+}
+
+impl Tdx for ExInstrumentCount {
+    type Item = u32;
+
+    /// 合约数量的请求字节，无查询参数。
+    const SEND: &'static [u8] = &[
+        0x01, 0x03, 0x48, 0x66, 0x00, 0x01, 0x02, 0x00, 0x02, 0x00, 0xf0, 0x23,
+    ];
+    const TAG: &'static str = "扩展行情合约数量";
+
+    fn send(&self) -> &[u8] {
+        Self::SEND
+    }
+
+    fn parse(&mut self, response: Vec<u8>) -> Result<()> {
+        self.count = self.request().decode(&response)?;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.count
+    }
+}
+
+/// [`ExInstrumentCount`] 的请求，无查询参数。见 [`Request`] 。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExInstrumentCountRequest;
+
+impl Request for ExInstrumentCountRequest {
+    const TAG: &'static str = <ExInstrumentCount as Tdx>::TAG;
+    type Response = u32;
+
+    fn send(&self) -> Box<[u8]> {
+        ExInstrumentCount::SEND.into()
+    }
+
+    /// 跳过前 19 个未知字节，之后 4 字节为合约数量。
+    fn decode(&self, response: &[u8]) -> Result<u32> {
+        ensure_len(Self::TAG, response, 23)?;
+        Ok(u32_from_le_bytes(response, 19))
+    }
+}
+
+/// 查询扩展行情的合约列表。对应于 pytdx 中的 exhq.get_instrument_info、ExGetInstrumentInfo。
+///
+/// ## 注意
+/// start 在 [0, n] 的范围内，其中 n 是 [`ExInstrumentCount`] 得到的结果。
+/// 每次最多返回 count 条结果。
+#[derive(Debug, Clone)]
+pub struct ExInstrumentList {
+    pub send: Box<[u8]>,
+    pub start: u32,
+    pub count: u16,
+    pub response: Vec<u8>,
+    pub data: Vec<ExInstrumentData>,
+}
+
+/// 默认从第 0 个开始查询 100 条。
+impl Default for ExInstrumentList {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            start: 0,
+            count: 100,
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl ExInstrumentList {
+    pub fn new(start: u32, count: u16) -> Self {
+        let mut list = Self::default();
+        list.start(start).count(count);
+        list
+    }
+
+    /// 修改起始位置。
+    pub fn start(&mut self, start: u32) -> &mut Self {
+        self.start = start;
+        self.send[12..16].copy_from_slice(&start.to_le_bytes());
+        self
+    }
+
+    /// 修改查询数量。
+    pub fn count(&mut self, count: u16) -> &mut Self {
+        self.count = count;
+        self.send[16..18].copy_from_slice(&count.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> ExInstrumentListRequest {
+        ExInstrumentListRequest::new(self.start, self.count)
+    }
+}
+
+impl Tdx for ExInstrumentList {
+    type Item = [ExInstrumentData];
+
+    /// start = 0, count = 100 的请求字节。长度为 18。
+    /// ```python
+    /// struct.pack("<IH", start, count) # 后 6 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x01, 0x04, 0x48, 0x67, 0x00, 0x01, 0x08, 0x00, 0x08, 0x00, 0xf5, 0x23, 0x00, 0x00, 0x00,
+        0x00, 0x64, 0x00,
+    ];
+    const TAG: &'static str = "扩展行情合约列表";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`ExInstrumentList`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExInstrumentListRequest {
+    pub start: u32,
+    pub count: u16,
+}
+
+impl ExInstrumentListRequest {
+    pub fn new(start: u32, count: u16) -> Self {
+        Self { start, count }
+    }
+}
+
+impl Request for ExInstrumentListRequest {
+    const TAG: &'static str = <ExInstrumentList as Tdx>::TAG;
+    type Response = Vec<ExInstrumentData>;
+
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; ExInstrumentList::LEN];
+        arr.copy_from_slice(ExInstrumentList::SEND);
+        arr[12..16].copy_from_slice(&self.start.to_le_bytes());
+        arr[16..18].copy_from_slice(&self.count.to_le_bytes());
+        arr.into()
+    }
+
+    /// 前 4 字节为起始位置，之后 2 字节表示列表的长度，
+    /// 剩余字节中，每 64 字节使用 [`ExInstrumentData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 6)?;
+        let count = u16_from_le_bytes(v, 4) as usize;
+        let data: Vec<_> = v[6..]
+            .chunks(64)
+            .filter(|b| b.len() >= 40)
+            .take(count)
+            .map(ExInstrumentData::parse)
+            .collect();
+        if data.len() < count {
+            let reason = format!("合约数量为 {count}，只解析到 {} 个", data.len());
+            return Err(Error::parse(Self::TAG, reason));
+        }
+        Ok(data)
+    }
+}
+
+/// [`ExInstrumentList`] 的解析结果。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ExInstrumentData {
+    /// 市场类别
+    pub category: u8,
+    /// 市场代码，对应于 [`ExMarketData::market`]
+    pub market: u8,
+    /// 合约代码，比如 `IFL8`
+    pub code: String,
+    /// 合约名称
+    pub name: String,
+    pub desc: String,
+}
+
+impl ExInstrumentData {
+    /// 解析 [`ExInstrumentList`] 的响应字节。传入长度至少为 40 字节的序列（每条记录占 64 字节，
+    /// 只有前 40 字节有意义）。
+    /// ```python
+    /// (category, market, _, code, name, desc) = struct.unpack("<BB3s9s17s9s", bytes[:40])
+    /// ```
+    /// 其中字符串都为 GBK 编码。
+    pub fn parse(bytes: &[u8]) -> Self {
+        Self {
+            category: bytes[0],
+            market: bytes[1],
+            code: gbk(&bytes[5..14]),
+            name: gbk(&bytes[14..31]),
+            desc: gbk(&bytes[31..40]),
+        }
+    }
+}
+
+#[test]
+fn new_modify() {
+    let list = ExInstrumentList::new(0, 100);
+    let mut list2 = ExInstrumentList::new(100, 1);
+    crate::tcp::tests::same_request(&list2, &list2.request());
+    list2.start(0).count(100);
+    compare!(ExInstrumentList::default(), list, list2);
+
+    let markets = ExMarketList::default();
+    crate::tcp::tests::same_request(&markets, &markets.request());
+    let count = ExInstrumentCount::default();
+    crate::tcp::tests::same_request(&count, &count.request());
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::exhq_connection(ExMarketList::default())?;
+    crate::tcp::tests::exhq_connection(ExInstrumentCount::default())?;
+    crate::tcp::tests::exhq_connection(ExInstrumentList::default())
+}
+
+#[test]
+fn parse() {
+    let gbk = |s: &str, len: usize| {
+        let mut bytes = encoding_rs::GBK.encode(s).0.into_owned();
+        bytes.resize(len, 0);
+        bytes
+    };
+
+    let mut markets = ExMarketList::default();
+    let mut arr = vec![0x02, 0x00];
+    for (category, name, market, short_name) in [(0, "", 0, ""), (3, "中金所期货", 47, "CZ")] {
+        arr.push(category);
+        arr.extend(gbk(name, 32));
+        arr.push(market);
+        arr.extend(gbk(short_name, 2));
+        arr.extend([0; 28]);
+    }
+    markets.parse(arr).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(markets.data, [
+        ExMarketData { market: 47, category: 3, name: "中金所期货".into(), short_name: "CZ".into() }
+    ]);
+
+    let mut count = ExInstrumentCount::default();
+    let mut arr = vec![0; 19];
+    arr.extend(73642u32.to_le_bytes());
+    count.parse(arr).unwrap();
+    assert_eq!(*count.result(), 73642);
+
+    let mut list = ExInstrumentList::default();
+    let mut arr = vec![0x00, 0x00, 0x00, 0x00, 0x01, 0x00];
+    arr.extend([3, 47, 0, 0, 0]);
+    arr.extend(gbk("IFL8", 9));
+    arr.extend(gbk("沪深主连", 17));
+    arr.extend(gbk("", 9 + 24));
+    list.parse(arr).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(list.data, [
+        ExInstrumentData { category: 3, market: 47, code: "IFL8".into(), name: "沪深主连".into(), desc: "".into() }
+    ]);
+}
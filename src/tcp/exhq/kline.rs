@@ -0,0 +1,303 @@
+use crate::tcp::{ensure_len, helper::DateTime, KlineCategory, Request, Result, Tdx};
+
+/// 查询扩展行情合约的 K 线（比如期货日线、分钟线）。
+/// 对应于 pytdx 中的 exhq.get_instrument_bars、ExGetInstrumentBars。
+///
+/// category 与 [`Kline`][crate::tcp::stock::Kline] 相同，见 [`KlineCategory`] 。
+/// ## 注意
+/// 只修改字段并不会更改待发送字节的内容。
+/// 如果你需要修改查询条件，请使用相应的方法。
+#[derive(Debug, Clone)]
+pub struct ExKline<'d> {
+    pub send: Box<[u8]>,
+    /// 市场代码，来自于 [`ExMarketList`][super::ExMarketList]，比如 47 表示中金所期货
+    pub market: u8,
+    /// 合约代码，比如 `IFL8`
+    pub code: &'d str,
+    pub category: KlineCategory,
+    pub start: u32,
+    pub count: u16,
+    pub response: Vec<u8>,
+    pub data: Vec<ExKlineData>,
+}
+
+/// 为了对应 [`ExKline::SEND`] 的含义，默认查询 #47 IFL8# 最近三天的日线。
+#[rustfmt::skip]
+impl<'d> Default for ExKline<'d> {
+    fn default() -> Self {
+        Self { market: 47, code: "IFL8", category: KlineCategory::Day, start: 0, count: 3,
+               send:     { let mut v = [0; Self::LEN]; v.copy_from_slice(Self::SEND); v.into() },
+               response: Vec::new(),
+               data:     Vec::new(), }
+    }
+}
+
+impl<'d> ExKline<'d> {
+    /// ## panic
+    /// 当 code 的字节长度超过 9 时，程序会 panic。
+    pub fn new(market: u8, code: &'d str, category: KlineCategory, start: u32, count: u16) -> Self {
+        let mut kline = Self::default();
+        kline
+            .market(market)
+            .code(code)
+            .category(category)
+            .start(start)
+            .count(count);
+        kline
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: u8) -> &mut Self {
+        self.market = market;
+        self.send[12] = market;
+        self
+    }
+
+    /// 修改合约。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度超过 9 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        let arr = &mut self.send[13..22];
+        arr.fill(0);
+        arr[..code.len()].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 修改 K 线类型。
+    pub fn category(&mut self, category: KlineCategory) -> &mut Self {
+        self.category = category;
+        self.send[22..24].copy_from_slice(&category.code().to_le_bytes());
+        self
+    }
+
+    /// 修改起始位置。
+    pub fn start(&mut self, start: u32) -> &mut Self {
+        self.start = start;
+        self.send[26..30].copy_from_slice(&start.to_le_bytes());
+        self
+    }
+
+    /// 修改查询数量。
+    pub fn count(&mut self, count: u16) -> &mut Self {
+        self.count = count;
+        self.send[30..32].copy_from_slice(&count.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> ExKlineRequest {
+        ExKlineRequest::new(
+            self.market,
+            self.code,
+            self.category,
+            self.start,
+            self.count,
+        )
+    }
+}
+
+impl<'a> Tdx for ExKline<'a> {
+    type Item = [ExKlineData];
+
+    /// #47 IFL8# 最近三天日线的请求字节。长度为 32。
+    /// ```python
+    /// struct.pack("<B9sHHIH", market, code, category, 1, start, count) # 后 20 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x01, 0x01, 0x08, 0x6a, 0x01, 0x01, 0x16, 0x00, 0x16, 0x00, 0xff, 0x23, 0x2f, 0x49, 0x46,
+        0x4c, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x03, 0x00,
+    ];
+    const TAG: &'static str = "扩展行情日线";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`ExKline`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExKlineRequest {
+    pub market: u8,
+    pub code: String,
+    pub category: KlineCategory,
+    pub start: u32,
+    pub count: u16,
+}
+
+impl ExKlineRequest {
+    pub fn new(
+        market: u8,
+        code: impl Into<String>,
+        category: KlineCategory,
+        start: u32,
+        count: u16,
+    ) -> Self {
+        Self {
+            market,
+            code: code.into(),
+            category,
+            start,
+            count,
+        }
+    }
+}
+
+impl Request for ExKlineRequest {
+    const TAG: &'static str = <ExKline as Tdx>::TAG;
+    type Response = Vec<ExKlineData>;
+
+    /// ## panic
+    /// 当 code 的字节长度超过 9 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; ExKline::LEN];
+        arr.copy_from_slice(ExKline::SEND);
+        arr[12] = self.market;
+        arr[13..22].fill(0);
+        arr[13..13 + self.code.len()].copy_from_slice(self.code.as_bytes());
+        arr[22..24].copy_from_slice(&self.category.code().to_le_bytes());
+        arr[26..30].copy_from_slice(&self.start.to_le_bytes());
+        arr[30..32].copy_from_slice(&self.count.to_le_bytes());
+        arr.into()
+    }
+
+    /// 跳过前 18 个未知字节，之后 2 字节表示 K 线数量，
+    /// 剩余字节中，每 32 字节使用 [`ExKlineData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 20)?;
+        let count = crate::bytes_helper::u16_from_le_bytes(v, 18) as usize;
+        ensure_len(Self::TAG, v, 20 + count * 32)?;
+        Ok(v[20..]
+            .chunks_exact(32)
+            .take(count)
+            .map(|b| ExKlineData::parse(b, &self.code, self.category))
+            .collect())
+    }
+}
+
+/// [`ExKline`] 的解析结果。与 [`KlineData`][crate::tcp::stock::KlineData] 不同，
+/// 扩展行情中的价格为 f32 。
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub struct ExKlineData {
+    pub dt: DateTime,
+    pub code: Box<str>,
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+    /// 持仓量（期货）
+    pub position: u32,
+    /// 成交量
+    pub trade: u32,
+    /// 结算价（期货）
+    pub price: f32,
+    /// 成交额（港股等）。与 position 是相同的 4 个字节，只是解读为 f32 。
+    pub amount: f32,
+}
+
+impl ExKlineData {
+    /// 解析一根 K 线。传入长度为 32 字节序列。
+    /// ```python
+    /// (open, high, low, close, position, trade, price) = struct.unpack("<ffffIIf", bytes[4:])
+    /// ```
+    /// 前 4 字节为日期时间，使用 [`datetime`][crate::tcp::helper::datetime] 解析。
+    pub fn parse(bytes: &[u8], code: &str, category: KlineCategory) -> Self {
+        use crate::bytes_helper::{f32_from_le_bytes, u32_from_le_bytes};
+        Self {
+            dt: crate::tcp::helper::datetime(&bytes[..4], category),
+            code: code.into(),
+            open: f32_from_le_bytes(bytes, 4),
+            high: f32_from_le_bytes(bytes, 8),
+            low: f32_from_le_bytes(bytes, 12),
+            close: f32_from_le_bytes(bytes, 16),
+            position: u32_from_le_bytes(bytes, 20),
+            trade: u32_from_le_bytes(bytes, 24),
+            price: f32_from_le_bytes(bytes, 28),
+            amount: f32_from_le_bytes(bytes, 20),
+        }
+    }
+}
+
+#[test]
+fn new_modify() {
+    use KlineCategory::{Day, ExMin1};
+    let kline = ExKline::new(47, "IFL8", Day, 0, 3);
+    let mut kline2 = ExKline::new(28, "CU2112X", ExMin1, 100, 800);
+    crate::tcp::tests::same_request(&kline2, &kline2.request());
+    kline2
+        .market(47)
+        .code("IFL8")
+        .category(Day)
+        .start(0)
+        .count(3);
+    compare!(ExKline::default(), kline, kline2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::exhq_connection(ExKline::default())
+}
+
+#[test]
+fn parse() {
+    let mut kline = ExKline::default();
+    let mut arr = vec![0; 18];
+    arr.extend(2u16.to_le_bytes());
+    for (date, open, high, low, close, position, trade, price) in [
+        (
+            20210923u32,
+            4880.2f32,
+            4912.0f32,
+            4850.4f32,
+            4895.6f32,
+            112345u32,
+            80021u32,
+            4890.2f32,
+        ),
+        (
+            20210924, 4895.0, 4901.8, 4860.0, 4866.4, 113020, 76540, 4870.0,
+        ),
+    ] {
+        arr.extend(date.to_le_bytes());
+        arr.extend(
+            [open, high, low, close]
+                .into_iter()
+                .flat_map(|f| f.to_le_bytes()),
+        );
+        arr.extend(position.to_le_bytes());
+        arr.extend(trade.to_le_bytes());
+        arr.extend(price.to_le_bytes());
+    }
+    kline.parse(arr).unwrap();
+
+    let dt = |day| DateTime {
+        year: 2021,
+        month: 9,
+        day,
+        ..Default::default()
+    };
+    assert_eq!(kline.data.len(), 2);
+    assert_eq!(kline.data[0].dt, dt(23));
+    assert_eq!(&*kline.data[0].code, "IFL8");
+    #[rustfmt::skip]
+    assert_eq!([kline.data[0].open, kline.data[0].high, kline.data[0].low, kline.data[0].close],
+               [4880.2, 4912.0, 4850.4, 4895.6]);
+    assert_eq!(
+        (kline.data[0].position, kline.data[0].trade),
+        (112345, 80021)
+    );
+    assert_eq!(kline.data[1].dt, dt(24));
+    assert_eq!((kline.data[1].close, kline.data[1].price), (4866.4, 4870.0));
+}
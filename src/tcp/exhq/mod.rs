@@ -0,0 +1,70 @@
+//! 扩展行情（端口一般为 7727），包括期货、港股、期权等非 A 股市场。
+//!
+//! 对应于 pytdx 的 exhq 部分。扩展行情服务器与 A 股行情服务器（[`STOCK_IP`][crate::tcp::ip::STOCK_IP]）
+//! 不同，握手包也不同，所以需要使用 [`Tcp::new_exhq`] 建立连接。
+//!
+//! 使用方式与 A 股行情相同：
+//! ```rust,no_run
+//! use rustdx::tcp::{exhq::ExMarketList, Tcp, Tdx};
+//! let mut tcp = Tcp::new_exhq()?;
+//! let markets = ExMarketList::default().recv_parsed(&mut tcp)?.to_vec();
+//! # Ok::<(), rustdx::tcp::Error>(())
+//! ```
+
+use super::{send_recv, Result, Tcp};
+use std::net::SocketAddr;
+
+mod basic;
+pub use basic::*;
+mod kline;
+pub use kline::{ExKline, ExKlineData, ExKlineRequest};
+mod quotes;
+pub use quotes::{ExQuotes, ExQuotesData, ExQuotesRequest};
+
+lazy_static::lazy_static! {
+    /// 扩展行情服务器地址。来源于 pytdx/util/best_ip.py 中的 future_ip 。
+    pub static ref EXHQ_IP: [SocketAddr; 12] = [
+        "106.14.95.149:7727".parse().unwrap(),
+        "112.74.214.43:7727".parse().unwrap(),
+        "119.147.86.171:7727".parse().unwrap(),
+        "119.97.185.5:7727".parse().unwrap(),
+        "120.24.0.77:7727".parse().unwrap(),
+        "124.74.236.94:7721".parse().unwrap(),
+        "202.103.36.71:443".parse().unwrap(),
+        "47.92.127.181:7727".parse().unwrap(),
+        "59.175.238.38:7727".parse().unwrap(),
+        "61.152.107.141:7727".parse().unwrap(),
+        "61.152.107.171:7727".parse().unwrap(),
+        "47.107.75.159:7727".parse().unwrap(),
+    ];
+}
+
+/// 扩展行情的握手包。对应于 pytdx 的 ExSetupCmd1 。
+pub const EX_PACK: &[u8] = &[
+    0x01, 0x01, 0x48, 0x65, 0x00, 0x01, 0x52, 0x00, 0x52, 0x00, 0x54, 0x24, 0x1f, 0x32, 0xc6, 0xe5,
+    0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5, 0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5,
+    0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5, 0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5,
+    0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5, 0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5,
+    0xd5, 0x3d, 0xfb, 0x41, 0x1f, 0x32, 0xc6, 0xe5, 0xd5, 0x3d, 0xfb, 0x41, 0xcc, 0xe1, 0x6d, 0xff,
+    0xd5, 0xba, 0x3f, 0xb8, 0xcb, 0xc5, 0x7a, 0x05, 0x4f, 0x77, 0x48, 0xea,
+];
+
+/// 发送扩展行情的握手包。
+pub fn send_packs(tcp: &mut Tcp) -> Result<()> {
+    send_recv(tcp, EX_PACK, "EX_PACK")?;
+    Ok(())
+}
+
+impl Tcp {
+    /// 连接第一个扩展行情服务器。已发送握手包。
+    pub fn new_exhq() -> Result<Self> {
+        Self::new_exhq_with_ip(&EXHQ_IP[0])
+    }
+
+    /// 连接指定的扩展行情服务器。已发送握手包。
+    pub fn new_exhq_with_ip(ip: &SocketAddr) -> Result<Self> {
+        let mut tcp = Self::builder().packs(false).connect(ip)?;
+        send_packs(&mut tcp)?;
+        Ok(tcp)
+    }
+}
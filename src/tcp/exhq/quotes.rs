@@ -0,0 +1,270 @@
+use crate::tcp::{Request, Result, Tdx};
+
+/// 查询扩展行情合约的实时行情快照（五档盘口）。
+/// 对应于 pytdx 中的 exhq.get_instrument_quote、ExGetInstrumentQuote。
+#[derive(Debug, Clone)]
+pub struct ExQuotes<'d> {
+    pub send: Box<[u8]>,
+    /// 市场代码，来自于 [`ExMarketList`][super::ExMarketList]
+    pub market: u8,
+    /// 合约代码，比如 `IFL8`
+    pub code: &'d str,
+    pub response: Vec<u8>,
+    /// 合约代码有误时，响应中没有数据，此时为 None 。
+    pub data: Option<ExQuotesData>,
+}
+
+/// 默认查询 #47 IFL8# 。
+impl<'d> Default for ExQuotes<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: 47,
+            code: "IFL8",
+            response: Vec::new(),
+            data: None,
+        }
+    }
+}
+
+impl<'d> ExQuotes<'d> {
+    /// ## panic
+    /// 当 code 的字节长度超过 9 时，程序会 panic。
+    pub fn new(market: u8, code: &'d str) -> Self {
+        let mut quotes = Self::default();
+        quotes.market(market).code(code);
+        quotes
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: u8) -> &mut Self {
+        self.market = market;
+        self.send[12] = market;
+        self
+    }
+
+    /// 修改合约。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度超过 9 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        let arr = &mut self.send[13..22];
+        arr.fill(0);
+        arr[..code.len()].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> ExQuotesRequest {
+        ExQuotesRequest::new(self.market, self.code)
+    }
+}
+
+impl<'a> Tdx for ExQuotes<'a> {
+    type Item = Option<ExQuotesData>;
+
+    /// #47 IFL8# 行情快照的请求字节。长度为 22。
+    /// ```python
+    /// struct.pack("<B9s", market, code) # 后 10 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x01, 0x01, 0x08, 0x02, 0x02, 0x01, 0x0c, 0x00, 0x0c, 0x00, 0xfa, 0x23, 0x2f, 0x49, 0x46,
+        0x4c, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    const TAG: &'static str = "扩展行情快照";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`ExQuotes`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExQuotesRequest {
+    pub market: u8,
+    pub code: String,
+}
+
+impl ExQuotesRequest {
+    pub fn new(market: u8, code: impl Into<String>) -> Self {
+        Self {
+            market,
+            code: code.into(),
+        }
+    }
+}
+
+impl Request for ExQuotesRequest {
+    const TAG: &'static str = <ExQuotes as Tdx>::TAG;
+    type Response = Option<ExQuotesData>;
+
+    /// ## panic
+    /// 当 code 的字节长度超过 9 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; ExQuotes::LEN];
+        arr[..13].copy_from_slice(&ExQuotes::SEND[..13]);
+        arr[12] = self.market;
+        arr[13..13 + self.code.len()].copy_from_slice(self.code.as_bytes());
+        arr.into()
+    }
+
+    /// 使用 [`ExQuotesData::parse`] 解析。响应字节过短时，说明没有数据。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        Ok((v.len() >= ExQuotesData::LEN).then(|| ExQuotesData::parse(v)))
+    }
+}
+
+/// [`ExQuotes`] 的解析结果。价格为 f32 。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ExQuotesData {
+    pub market: u8,
+    pub code: String,
+    /// 昨收（期货为昨结算）
+    pub preclose: f32,
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    /// 现价
+    pub price: f32,
+    /// 开仓
+    pub kaicang: u32,
+    /// 总量
+    pub zongliang: u32,
+    /// 现量
+    pub xianliang: u32,
+    /// 内盘
+    pub neipan: u32,
+    /// 外盘
+    pub waipan: u32,
+    /// 持仓
+    pub chicang: u32,
+    /// 买一至买五的价格
+    pub bid: [f32; 5],
+    /// 买一至买五的挂单量
+    pub bid_vol: [u32; 5],
+    /// 卖一至卖五的价格
+    pub ask: [f32; 5],
+    /// 卖一至卖五的挂单量
+    pub ask_vol: [u32; 5],
+}
+
+impl ExQuotesData {
+    /// 响应字节的最小长度。
+    pub const LEN: usize = 150;
+
+    /// 解析 [`ExQuotes`] 的响应字节。传入长度至少为 150 字节的序列。
+    /// ```python
+    /// (market, code) = struct.unpack("<B9s", bytes[:10]) # 之后跳过 4 字节
+    /// (pre_close, open, high, low, price, kaicang, _, zongliang, xianliang, _, neipan, waipan,
+    ///  _, chicang, bid1..5, bid_vol1..5, ask1..5, ask_vol1..5,
+    /// ) = struct.unpack("<5f9I5f5I5f5I", bytes[14:150]) # python 表示方式
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Self {
+        use crate::bytes_helper::{f32_from_le_bytes, u32_from_le_bytes};
+        // 从第 14 字节开始的第 n 个 4 字节值
+        let f = |n: usize| f32_from_le_bytes(bytes, 14 + n * 4);
+        let u = |n: usize| u32_from_le_bytes(bytes, 14 + n * 4);
+        Self {
+            market: bytes[0],
+            code: crate::tcp::helper::gbk(&bytes[1..10]),
+            preclose: f(0),
+            open: f(1),
+            high: f(2),
+            low: f(3),
+            price: f(4),
+            kaicang: u(5),
+            zongliang: u(7),
+            xianliang: u(8),
+            neipan: u(10),
+            waipan: u(11),
+            chicang: u(13),
+            bid: std::array::from_fn(|i| f(14 + i)),
+            bid_vol: std::array::from_fn(|i| u(19 + i)),
+            ask: std::array::from_fn(|i| f(24 + i)),
+            ask_vol: std::array::from_fn(|i| u(29 + i)),
+        }
+    }
+}
+
+#[test]
+fn new_modify() {
+    let quotes = ExQuotes::new(47, "IFL8");
+    let mut quotes2 = ExQuotes::new(28, "CU2112X");
+    crate::tcp::tests::same_request(&quotes2, &quotes2.request());
+    quotes2.market(47).code("IFL8");
+    compare!(ExQuotes::default(), quotes, quotes2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::exhq_connection(ExQuotes::default())
+}
+
+#[test]
+fn parse() {
+    let mut quotes = ExQuotes::default();
+    quotes.parse(vec![0; 10]).unwrap();
+    assert_eq!(quotes.result(), &None);
+
+    let mut arr = vec![47];
+    arr.extend(b"IFL8\0\0\0\0\0");
+    arr.extend([0; 4]);
+    arr.extend(
+        [4880.2f32, 4885.0, 4912.0, 4850.4, 4895.6]
+            .into_iter()
+            .flat_map(|f| f.to_le_bytes()),
+    );
+    arr.extend(
+        [3021u32, 0, 80021, 2, 0, 39870, 40151, 0, 112345]
+            .into_iter()
+            .flat_map(|u| u.to_le_bytes()),
+    );
+    for level in 0..5 {
+        arr.extend((4895.4f32 - level as f32).to_le_bytes());
+    }
+    arr.extend(
+        [12u32, 5, 8, 3, 9]
+            .into_iter()
+            .flat_map(|u| u.to_le_bytes()),
+    );
+    for level in 0..5 {
+        arr.extend((4895.8f32 + level as f32).to_le_bytes());
+    }
+    arr.extend(
+        [7u32, 4, 6, 10, 2]
+            .into_iter()
+            .flat_map(|u| u.to_le_bytes()),
+    );
+    quotes.parse(arr).unwrap();
+
+    let data = quotes.result().as_ref().unwrap();
+    assert_eq!((data.market, data.code.as_str()), (47, "IFL8"));
+    assert_eq!((data.preclose, data.price), (4880.2, 4895.6));
+    assert_eq!(
+        (data.kaicang, data.zongliang, data.xianliang),
+        (3021, 80021, 2)
+    );
+    assert_eq!(
+        (data.neipan, data.waipan, data.chicang),
+        (39870, 40151, 112345)
+    );
+    assert_eq!(data.bid[0], 4895.4);
+    assert_eq!(data.bid_vol, [12, 5, 8, 3, 9]);
+    assert_eq!(data.ask[4], 4899.8);
+    assert_eq!(data.ask_vol, [7, 4, 6, 10, 2]);
+}
@@ -0,0 +1,127 @@
+use super::{Error, Result, RECV_SIZE};
+use crate::bytes_helper::{u16_from_le_bytes, u32_from_le_bytes};
+
+/// 16 字节的响应信息。
+///
+/// | 位置 | 0..4          | 4    | 5..9 | 9    | 10..12   | 12..14     | 14..16     |
+/// | ---- | ------------- | ---- | ---- | ---- | -------- | ---------- | ---------- |
+/// | 含义 | `b1 cb 74 00` | 标志 | 序号 | 未知 | 请求类型 | 待解压长度 | 解压后长度 |
+///
+/// 标志、序号和请求类型与请求字节中的相同：请求字节的第 0 字节为标志，1..5 为序号，
+/// 10..12 为请求类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseHeader {
+    /// 与请求字节的第 0 字节相同（A 股行情为 `0x0c`，扩展行情为 `0x01`）
+    pub flag: u8,
+    pub seq: u32,
+    pub cmd: u16,
+    pub deflate_size: u16,
+    pub inflate_size: u16,
+}
+
+impl ResponseHeader {
+    /// 响应信息的前 4 字节。
+    pub const MAGIC: [u8; 4] = [0xb1, 0xcb, 0x74, 0x00];
+
+    /// 解析响应信息。不以 [`ResponseHeader::MAGIC`] 开头时返回 [`Error::BadHeader`] 。
+    pub fn parse(header: &[u8; RECV_SIZE]) -> Result<Self> {
+        if header[..4] != Self::MAGIC {
+            return Err(Error::BadHeader(*header));
+        }
+        Ok(Self {
+            flag: header[4],
+            seq: u32_from_le_bytes(header, 5),
+            cmd: u16_from_le_bytes(header, 10),
+            deflate_size: u16_from_le_bytes(header, 12),
+            inflate_size: u16_from_le_bytes(header, 14),
+        })
+    }
+
+    /// 有效数据是否经过压缩。
+    pub fn is_compressed(&self) -> bool {
+        self.deflate_size != self.inflate_size
+    }
+
+    /// 检查响应的序号和请求类型是否与请求字节相同。
+    ///
+    /// 不同时，说明读到的是其他请求（比如上一次超时的请求）的响应，返回
+    /// [`Error::SeqMismatch`] 或 [`Error::CmdMismatch`] 。
+    pub fn check(&self, request: &[u8]) -> Result<()> {
+        let (seq, cmd) = (request_seq(request), u16_from_le_bytes(request, 10));
+        if self.seq != seq {
+            return Err(Error::SeqMismatch {
+                expected: seq,
+                found: self.seq,
+            });
+        }
+        if self.cmd != cmd {
+            return Err(Error::CmdMismatch {
+                expected: cmd,
+                found: self.cmd,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// 请求字节中的序号（1..5 字节）。
+pub fn request_seq(request: &[u8]) -> u32 {
+    u32_from_le_bytes(request, 1)
+}
+
+/// 复制请求字节，并把序号修改为 seq 。
+pub fn with_seq(request: &[u8], seq: u32) -> Vec<u8> {
+    let mut send = request.to_vec();
+    send[1..5].copy_from_slice(&seq.to_le_bytes());
+    send
+}
+
+#[test]
+fn parse() {
+    use super::{SecurityCount, SecurityList, Tdx};
+
+    let request = with_seq(SecurityCount::SEND, 0x12345678);
+    assert_eq!(request_seq(&request), 0x12345678);
+    assert_eq!(request[5..], SecurityCount::SEND[5..]);
+
+    #[rustfmt::skip]
+    let mut bytes = [0xb1, 0xcb, 0x74, 0x00, 0x0c, 0x78, 0x56, 0x34, 0x12, 0x00, 0x4e, 0x04,
+                     0x02, 0x00, 0x02, 0x00];
+    let header = ResponseHeader::parse(&bytes).unwrap();
+    assert_eq!(
+        header,
+        ResponseHeader {
+            flag: 0x0c,
+            seq: 0x12345678,
+            cmd: 0x044e,
+            deflate_size: 2,
+            inflate_size: 2,
+        }
+    );
+    assert!(!header.is_compressed());
+    header.check(&request).unwrap();
+
+    let err = header.check(SecurityCount::SEND).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::SeqMismatch {
+            found: 0x12345678,
+            ..
+        }
+    ));
+    let list = with_seq(SecurityList::SEND, 0x12345678);
+    let err = header.check(&list).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::CmdMismatch {
+            expected: 0x0450,
+            found: 0x044e
+        }
+    ));
+
+    bytes[0] = 0;
+    assert!(matches!(
+        ResponseHeader::parse(&bytes),
+        Err(Error::BadHeader(_))
+    ));
+}
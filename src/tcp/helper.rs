@@ -1,5 +1,6 @@
 //! 对应于 pytdx/helper.py 文件，用于辅助解析响应的字节数据。
 
+use super::{Error, KlineCategory, Result};
 use crate::bytes_helper::{u16_from_le_bytes, u32_from_le_bytes};
 
 /// 解析日期时间的原始结果。如果需要其他形式的日期时间，可自行转化。
@@ -7,7 +8,9 @@ use crate::bytes_helper::{u16_from_le_bytes, u32_from_le_bytes};
 /// 由函数 [`datetime`] 解析响应字节得到此结构体。
 ///
 /// 注意：默认 15 时（即 `DateTime::default().hour == 15`）。
-#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+///
+/// 字段的顺序保证了比较大小时按照时间先后顺序。
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
 pub struct DateTime {
     pub year: u16,
     pub month: u16,
@@ -23,13 +26,16 @@ impl Default for DateTime {
 }
 
 impl DateTime {
-    pub fn into_string(self, category: u16) -> String {
-        match category {
-            0..=3 | 7 | 8 => format!(
+    /// 带有时间的 K 线类型（见 [`KlineCategory::has_time`]）转化成 `YYYY-MM-DD hh:mm`，
+    /// 其他转化成 `YYYY-MM-DD` 。
+    pub fn into_string(self, category: KlineCategory) -> String {
+        if category.has_time() {
+            format!(
                 "{:04}-{:02}-{:02} {:02}:{:02}",
                 self.year, self.month, self.day, self.hour, self.minute
-            ),
-            _ => format!("{:04}-{:02}-{:02}", self.year, self.month, self.day),
+            )
+        } else {
+            format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
         }
     }
 
@@ -41,13 +47,13 @@ impl DateTime {
 
 /// 解析日期和时间（小时分钟）。
 ///
-/// 对于不需要时间的 category （比如 category = 9 的日线），以默认的 15:00 作为时间。
+/// 对于不需要时间的 category （比如 [`KlineCategory::Day`]），以默认的 15:00 作为时间。
 ///  
 /// 注意：参数 arr 为长度为 4 的 slice。
-pub fn datetime(arr: &[u8], category: u16) -> DateTime {
+pub fn datetime(arr: &[u8], category: KlineCategory) -> DateTime {
     let mut datetime = DateTime::default();
 
-    if category < 4 || category == 7 || category == 8 {
+    if category.has_time() {
         let day = u16_from_le_bytes(arr, 0);
         let minutes = u16_from_le_bytes(arr, 2);
         datetime.year = (day >> 11) + 2004;
@@ -69,26 +75,44 @@ pub fn datetime(arr: &[u8], category: u16) -> DateTime {
 ///
 /// 注意：
 /// 1. 第二次之后计算的价格为浮动价格，基于第一次解析的实际价格而浮动；
-/// 2. 返回的 pos 是不定长的。
-pub fn price(arr: &[u8], pos: &mut usize) -> i32 {
+/// 2. 返回的 pos 是不定长的；
+/// 3. 字节在价格结束之前用完，或者价格超出 i32 的范围时，返回 [`Error::Parse`] 。
+pub fn price(arr: &[u8], pos: &mut usize) -> Result<i32> {
+    const TAG: &str = "价格";
+    let byte = |pos: usize| {
+        arr.get(pos).map(|&b| b as i64).ok_or_else(|| {
+            Error::parse(
+                TAG,
+                format!("第 {pos} 字节超出有效数据的长度 {}", arr.len()),
+            )
+        })
+    };
+
     let mut shl = 6;
-    let mut bit = arr[*pos] as i32;
+    let mut bit = byte(*pos)?;
     let mut res = bit & 0x3f;
     let sign = (bit & 0x40) == 0;
 
+    let overflow = |pos: usize| Error::parse(TAG, format!("第 {pos} 字节处的价格超出 i32 的范围"));
     while (bit & 0x80) != 0 {
         *pos += 1;
-        bit = arr[*pos] as i32;
+        // i32 最多需要 5 字节（6 + 7 * 4 位），之后的字节只会超出 i32 的范围
+        if shl > 27 {
+            return Err(overflow(*pos));
+        }
+        bit = byte(*pos)?;
         res += (bit & 0x7f) << shl;
         shl += 7;
     }
+    let res = i32::try_from(if sign { res } else { -res }).map_err(|_| overflow(*pos));
     *pos += 1;
+    res
+}
 
-    if sign {
-        res
-    } else {
-        -res
-    }
+/// 在 base 的基础上加上浮动价格 p 。超出 i32 的范围时返回 [`Error::Parse`] 。
+pub fn add_price(base: i32, p: i32) -> Result<i32> {
+    base.checked_add(p)
+        .ok_or_else(|| Error::parse("价格", format!("{base} + {p} 超出 i32 的范围")))
 }
 
 pub fn vol_amount(ivol: i32) -> f64 {
@@ -131,6 +155,13 @@ pub fn vol_amount(ivol: i32) -> f64 {
     dbl_xmm6 + dbl_xmm4 + dbl_xmm3 + dbl_xmm1
 }
 
+/// 解析以 `\0` 结尾（或填充）的 GBK 编码字符串。无法解码的字节会被替换成 `U+FFFD`。
+pub fn gbk(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let (s, _, _) = encoding_rs::GBK.decode(&bytes[..end]);
+    s.into()
+}
+
 // def get_time(buffer, pos):
 //     (tminutes, ) = struct.unpack("<H", buffer[pos: pos + 2])
 //     hour = int(tminutes / 60)
@@ -142,15 +173,44 @@ pub fn vol_amount(ivol: i32) -> f64 {
 #[test]
 fn check_datetime_price_vol_amount() {
     #[rustfmt::skip]
-    assert_eq!(datetime(&[235, 100, 52, 1], 9),
+    assert_eq!(datetime(&[235, 100, 52, 1], KlineCategory::Day),
                DateTime { year: 2021, month: 9, day: 23, hour: 15, minute: 0, });
-    assert_eq!(price(&[180, 154, 2], &mut 0), 18100);
-    assert_eq!(price(&[228, 6], &mut 0), -420);
-    assert_eq!(price(&[156, 3], &mut 0), 220);
-    assert_eq!(price(&[194, 7], &mut 0), -450);
+    assert_eq!(price(&[180, 154, 2], &mut 0).unwrap(), 18100);
+    assert_eq!(price(&[228, 6], &mut 0).unwrap(), -420);
+    assert_eq!(price(&[156, 3], &mut 0).unwrap(), 220);
+    assert_eq!(price(&[194, 7], &mut 0).unwrap(), -450);
     assert_eq!(vol_amount(1235775464), 1379837.0);
     assert_eq!(vol_amount(1326643033), 2465683712.0);
 
+    // 字节在价格结束之前用完
+    for arr in [&[][..], &[180], &[180, 154]] {
+        let err = price(arr, &mut 0).unwrap_err();
+        assert!(
+            matches!(err, Error::Parse { tag: "价格", .. }),
+            "{arr:?}: {err}"
+        );
+    }
+    // 超出 i32 的范围
+    for arr in [&[0x80; 8][..], &[0x80, 0x80, 0x80, 0x80, 0x10]] {
+        let err = price(arr, &mut 0).unwrap_err();
+        assert!(
+            err.to_string().contains("超出 i32 的范围"),
+            "{arr:?}: {err}"
+        );
+    }
+    // -2^31 仍在 i32 的范围内
+    assert_eq!(
+        price(&[0xc0, 0x80, 0x80, 0x80, 0x10], &mut 0).unwrap(),
+        i32::MIN
+    );
+    assert_eq!(add_price(18100, -420).unwrap(), 17680);
+    assert!(add_price(i32::MAX, 1).is_err());
+    for p in [i32::MAX, i32::MIN] {
+        let mut v = Vec::new();
+        crate::tcp::mock::encode_price(p, &mut v);
+        assert_eq!(price(&v, &mut 0).unwrap(), p);
+    }
+
     // let arr = [235, 100, 52, 1, 180, 154, 2, 228, 6, 156, 3, 194, 7, 232, 111, 168, 73, 89,
     // 247, 18, 79];
 }
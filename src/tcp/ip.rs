@@ -13,8 +13,15 @@
 //! 1. <https://gitee.com/ibopo/mootdx/blob/master/mootdx/consts.py>
 //! 2. 通达信客户端设置
 
+use super::{Market, SecurityCount, Tcp, Tdx};
+use std::{
+    net::SocketAddr,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
 lazy_static::lazy_static! {
-    pub static ref STOCK_IP: [std::net::SocketAddr; 19] = [
+    pub static ref STOCK_IP: [SocketAddr; 19] = [
         "39.100.68.59:7709".parse().unwrap(),
         "114.80.149.19:7709".parse().unwrap(),
         "114.80.149.22:7709".parse().unwrap(),
@@ -38,6 +45,95 @@ lazy_static::lazy_static! {
     ];
 }
 
+/// 服务器测速结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Latency {
+    pub addr: SocketAddr,
+    /// 心跳包的往返时间
+    pub rtt: Duration,
+}
+
+/// 对单个服务器测速：建立连接并发送测试包之后，计时发送一次心跳包（[`SecurityCount`]）。
+///
+/// 连接或心跳失败时返回 None 。
+pub fn probe(addr: &SocketAddr) -> Option<Latency> {
+    let mut tcp = Tcp::new_with_ip(addr).ok()?;
+    let now = Instant::now();
+    SecurityCount::new(Market::SZ).recv_parsed(&mut tcp).ok()?;
+    Some(Latency {
+        addr: *addr,
+        rtt: now.elapsed(),
+    })
+}
+
+/// 并发地对所有服务器测速，去除失败的地址，按往返时间从快到慢排序。
+/// 对应于 pytdx 中的 best_ip.select_best_ip 。
+pub fn best_ips(ips: &[SocketAddr]) -> Vec<Latency> {
+    let mut res: Vec<Latency> = std::thread::scope(|s| {
+        let handles: Vec<_> = ips.iter().map(|addr| s.spawn(|| probe(addr))).collect();
+        handles
+            .into_iter()
+            .filter_map(|h| h.join().ok().flatten())
+            .collect()
+    });
+    res.sort_by_key(|l| l.rtt);
+    res
+}
+
+/// 对 [`STOCK_IP`] 测速并排序，见 [`best_ips`] 。
+pub fn best_stock_ips() -> Vec<Latency> {
+    best_ips(&*STOCK_IP)
+}
+
+/// 所有服务器都测速失败之后，在这段时间内不再测速，[`fastest_stock_ips`] 直接返回空的结果。
+pub const PROBE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// [`best_stock_ips`] 的结果。第一次得到非空的结果之后缓存下来，之后直接返回缓存的结果。
+///
+/// [`Tcp::new`] 依次尝试其中的地址，所以第一次调用 [`Tcp::new`] 会阻塞到所有服务器测速完毕
+/// （最长为连接和读写的超时时间）。所有服务器都测速失败时返回空的结果，并在
+/// [`PROBE_COOLDOWN`] 内不再测速（比如断网时，不会每次 [`Tcp::new`] 都等待所有服务器超时）。
+pub fn fastest_stock_ips() -> &'static [Latency] {
+    static FASTEST: Ranking = Ranking::new();
+    FASTEST.get(PROBE_COOLDOWN, best_stock_ips)
+}
+
+/// 测速结果的缓存：成功的结果一直缓存，失败（空的结果）只在冷却期内缓存。
+struct Ranking {
+    ranked: OnceLock<Vec<Latency>>,
+    /// 上一次测速失败的时间。同时用来保证同一时间只有一个线程在测速。
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl Ranking {
+    const fn new() -> Self {
+        Self {
+            ranked: OnceLock::new(),
+            failed_at: Mutex::new(None),
+        }
+    }
+
+    fn get(&self, cooldown: Duration, probe: impl FnOnce() -> Vec<Latency>) -> &[Latency] {
+        if let Some(ranked) = self.ranked.get() {
+            return ranked;
+        }
+        let mut failed_at = self.failed_at.lock().unwrap_or_else(|e| e.into_inner());
+        // 等待锁的期间，其他线程可能已经测速成功
+        if let Some(ranked) = self.ranked.get() {
+            return ranked;
+        }
+        if failed_at.is_some_and(|t| t.elapsed() < cooldown) {
+            return &[];
+        }
+        let ranked = probe();
+        if ranked.is_empty() {
+            *failed_at = Some(Instant::now());
+            return &[];
+        }
+        self.ranked.get_or_init(|| ranked)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::STOCK_IP;
@@ -53,4 +149,48 @@ mod tests {
         }
         assert_eq!(STOCK_IP.as_ref(), valid_addrs.as_slice());
     }
+
+    #[test]
+    #[ignore = "联网测速"]
+    fn best_stock_ips() {
+        let ranked = super::best_stock_ips();
+        assert!(!ranked.is_empty());
+        assert!(ranked.windows(2).all(|w| w[0].rtt <= w[1].rtt));
+    }
+
+    #[test]
+    fn ranking_cooldown() {
+        use super::{Latency, Ranking};
+        use std::{cell::Cell, time::Duration};
+
+        let latency = Latency {
+            addr: STOCK_IP[0],
+            rtt: Duration::from_millis(1),
+        };
+        let probes = Cell::new(0);
+        let probe = |res: Vec<Latency>| {
+            probes.set(probes.get() + 1);
+            res
+        };
+        let ranking = Ranking::new();
+        let cooldown = Duration::from_secs(60);
+        // 失败的结果在冷却期内不再测速
+        assert!(ranking.get(cooldown, || probe(vec![])).is_empty());
+        assert!(ranking.get(cooldown, || probe(vec![latency])).is_empty());
+        assert_eq!(probes.get(), 1);
+        // 冷却期之后重新测速，成功的结果一直缓存
+        assert_eq!(
+            ranking.get(Duration::ZERO, || probe(vec![latency])),
+            [latency]
+        );
+        assert_eq!(ranking.get(Duration::ZERO, || probe(vec![])), [latency]);
+        assert_eq!(probes.get(), 2);
+    }
+
+    #[test]
+    fn unreachable_ips() {
+        // 保留地址（RFC 5737），不会有服务器响应
+        let ips = ["192.0.2.1:7709".parse().unwrap()];
+        assert!(super::best_ips(&ips).is_empty());
+    }
 }
@@ -0,0 +1,180 @@
+//! 请求中的市场代码和 K 线类型。
+//!
+//! 服务器收到无效的市场代码或 K 线类型时，不会返回错误，而是返回空的结果，
+//! 所以请求只接受这里的枚举，而不是任意的整数。
+
+use super::{Error, Result};
+use std::{fmt, str::FromStr};
+
+/// 沪深京市场。对应请求字节中的市场代码。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u16)]
+pub enum Market {
+    /// 深市
+    #[default]
+    SZ = 0,
+    /// 沪市
+    SH = 1,
+    /// 北交所
+    BJ = 2,
+}
+
+impl Market {
+    const TAG: &'static str = "市场";
+
+    /// 请求字节中的市场代码。
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// 小写的市场前缀，比如 `sh` 。
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Market::SZ => "sz",
+            Market::SH => "sh",
+            Market::BJ => "bj",
+        }
+    }
+
+    /// 拆分带市场前缀的证券代码（不区分大小写），比如 `sh600000` 得到 `(Market::SH, "600000")`。
+    ///
+    /// ```rust
+    /// use rustdx::tcp::{stock::Kline, KlineCategory, Market};
+    ///
+    /// let (market, code) = Market::split_code("sh600000")?;
+    /// assert_eq!((market, code), (Market::SH, "600000"));
+    /// let kline = Kline::new(market, code, KlineCategory::Day, 0, 3);
+    /// assert!(Market::split_code("hk000700").is_err());
+    /// # Ok::<(), rustdx::tcp::Error>(())
+    /// ```
+    pub fn split_code(s: &str) -> Result<(Self, &str)> {
+        match (s.get(..2), s.get(2..)) {
+            (Some(prefix), Some(code)) if code.len() == 6 => Ok((prefix.parse()?, code)),
+            _ => Err(Error::parse(
+                Self::TAG,
+                format!("{s} 不是带市场前缀的 6 位证券代码"),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Market {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.prefix())
+    }
+}
+
+/// 解析市场前缀（不区分大小写），比如 `sh` 或 `SH` 。
+impl FromStr for Market {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sz" => Ok(Market::SZ),
+            "sh" => Ok(Market::SH),
+            "bj" => Ok(Market::BJ),
+            _ => Err(Error::parse(Self::TAG, format!("未知的市场前缀 {s}"))),
+        }
+    }
+}
+
+impl TryFrom<u16> for Market {
+    type Error = Error;
+
+    fn try_from(code: u16) -> Result<Self> {
+        match code {
+            0 => Ok(Market::SZ),
+            1 => Ok(Market::SH),
+            2 => Ok(Market::BJ),
+            _ => Err(Error::parse(Self::TAG, format!("未知的市场代码 {code}"))),
+        }
+    }
+}
+
+/// K 线类型。对应请求字节中的 category ，与 pytdx 的含义相同。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u16)]
+pub enum KlineCategory {
+    /// 5 分钟 K 线
+    Min5 = 0,
+    /// 15 分钟 K 线
+    Min15 = 1,
+    /// 30 分钟 K 线
+    Min30 = 2,
+    /// 1 小时 K 线
+    Hour = 3,
+    /// 日 K 线（与 [`Day`][KlineCategory::Day] 相同）
+    Daily = 4,
+    /// 周 K 线
+    Week = 5,
+    /// 月 K 线
+    Month = 6,
+    /// 1 分钟（扩展行情）
+    ExMin1 = 7,
+    /// 1 分钟 K 线
+    Min1 = 8,
+    /// 日 K 线
+    #[default]
+    Day = 9,
+    /// 季 K 线
+    Quarter = 10,
+    /// 年 K 线
+    Year = 11,
+}
+
+impl KlineCategory {
+    /// 请求字节中的 category 。
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// 响应中的日期是否带有时间（小时分钟）。分钟和小时 K 线为 true 。
+    pub const fn has_time(self) -> bool {
+        use KlineCategory::*;
+        matches!(self, Min5 | Min15 | Min30 | Hour | ExMin1 | Min1)
+    }
+}
+
+impl TryFrom<u16> for KlineCategory {
+    type Error = Error;
+
+    fn try_from(code: u16) -> Result<Self> {
+        use KlineCategory::*;
+        const ALL: [KlineCategory; 12] = [
+            Min5, Min15, Min30, Hour, Daily, Week, Month, ExMin1, Min1, Day, Quarter, Year,
+        ];
+        ALL.get(code as usize)
+            .copied()
+            .ok_or_else(|| Error::parse("K 线类型", format!("未知的 K 线类型 {code}")))
+    }
+}
+
+#[test]
+fn market() {
+    assert_eq!(
+        Market::split_code("SZ000001").unwrap(),
+        (Market::SZ, "000001")
+    );
+    assert_eq!(
+        Market::split_code("bj430047").unwrap(),
+        (Market::BJ, "430047")
+    );
+    assert!(Market::split_code("sh60000").is_err());
+    assert!(Market::split_code("600000").is_err());
+    assert_eq!(Market::try_from(1).unwrap(), Market::SH);
+    assert!(Market::try_from(3).is_err());
+    assert_eq!(Market::SH.to_string(), "sh");
+    assert_eq!(
+        Market::split_code("hk000700").unwrap_err().to_string(),
+        "市场：解析失败，未知的市场前缀 hk"
+    );
+}
+
+#[test]
+fn kline_category() {
+    for code in 0..12 {
+        assert_eq!(KlineCategory::try_from(code).unwrap().code(), code);
+    }
+    assert!(KlineCategory::try_from(12).is_err());
+    assert!(KlineCategory::Min1.has_time() && !KlineCategory::Day.has_time());
+}
@@ -0,0 +1,331 @@
+//! 本地的模拟通达信服务器，用于离线测试。需要开启 `mock` feature 。
+//!
+//! [`MockServer`] 监听 `127.0.0.1` 的随机端口，按照真实的格式（16 字节响应信息 + zlib
+//! 压缩的有效数据）回复以下请求：
+//!
+//! | 请求               | 回复                                        |
+//! | ------------------ | ------------------------------------------- |
+//! | 三个测试包         | 固定字节                                    |
+//! | [`SecurityCount`]  | 深市 13471 ，沪市 18065                     |
+//! | [`SecurityList`]   | [`SECURITIES`] 中从 start 开始的证券        |
+//! | [`Kline`]          | [`BARS`] 中按 start 和 count 截取的 K 线    |
+//! | [`Xdxr`]           | 一条除权除息记录（见 [`XDXR`]）             |
+//!
+//! 其他请求会得到长度为 0 的有效数据。
+//!
+//! ```rust
+//! use rustdx::tcp::{mock::MockServer, stock::Kline, Tdx};
+//!
+//! let server = MockServer::start()?;
+//! let mut tcp = server.connect()?;
+//! let data = Kline::default().recv_parsed(&mut tcp)?.to_vec();
+//! assert_eq!(data.len(), 3);
+//! # Ok::<(), rustdx::tcp::Error>(())
+//! ```
+//!
+//! [`SecurityCount`]: super::SecurityCount
+//! [`SecurityList`]: super::SecurityList
+//! [`Kline`]: super::stock::Kline
+//! [`Xdxr`]: super::stock::Xdxr
+
+use super::{KlineCategory, Tcp};
+use crate::bytes_helper::u16_from_le_bytes;
+use std::{
+    io::{Read, Result, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+/// 模拟的证券列表：代码和 GBK 编码之前的名称（不超过 8 字节）。
+pub const SECURITIES: [(&str, &str); 3] = [
+    ("000001", "平安银行"),
+    ("000002", "万科Ａ"),
+    ("399001", "深证成指"),
+];
+
+/// 模拟的日线：`(YYYYMMDD, open, close, high, low)` ，价格放大了 1000 倍，按时间顺序排列。
+///
+/// 所有 K 线的成交量为 1379837 股，成交额为 2465683712 元。
+pub const BARS: [(u32, i32, i32, i32, i32); 5] = [
+    (20210917, 18100, 18220, 18300, 18000),
+    (20210922, 18200, 17960, 18250, 17900),
+    (20210923, 17990, 18410, 18500, 17950),
+    (20210924, 18400, 18150, 18460, 18080),
+    (20210927, 18150, 18530, 18600, 18100),
+];
+
+/// 模拟的除权除息记录，即 #sz000001# 在 19900301 的配股。
+pub const XDXR: [u8; 29] = [
+    0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x8d, 0xa7, 0x2f, 0x01, 0x01, 0x00, 0x00, 0x00,
+    0x00, 0x0a, 0xd7, 0x63, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x3f,
+];
+
+/// 本地的模拟服务器。drop 时停止监听。
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// 在 `127.0.0.1` 的随机端口上开始监听。每个连接在单独的线程中处理。
+    pub fn start() -> Result<Self> {
+        Self::start_with(respond)
+    }
+
+    /// 同 [`MockServer::start`] ，但使用 respond 根据请求字节生成完整的响应字节，
+    /// 比如对某个请求返回固定的有效数据（见 [`frame`]），其他请求交给 [`respond`] 。
+    pub fn start_with(respond: fn(&[u8]) -> Vec<u8>) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stopped = Arc::clone(&stopped);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::Acquire) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        std::thread::spawn(move || serve(stream, respond));
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            addr,
+            stopped,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 连接模拟服务器。已发送三个测试包。
+    pub fn connect(&self) -> super::Result<Tcp> {
+        Tcp::new_with_ip(&self.addr)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        // 连接一次，让监听线程从 accept 中返回
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 依次读取请求并回复，直到客户端断开。
+fn serve(mut stream: TcpStream, respond: fn(&[u8]) -> Vec<u8>) {
+    while let Some(request) = read_request(&mut stream) {
+        if stream.write_all(&respond(&request)).is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+/// 读取一个完整的请求：10 字节的请求信息，之后为 len 字节（2 字节请求类型 + 参数）。
+fn read_request(stream: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut request = vec![0; 10];
+    stream.read_exact(&mut request).ok()?;
+    let len = u16_from_le_bytes(&request, 6) as usize;
+    request.resize(10 + len, 0);
+    stream.read_exact(&mut request[10..]).ok()?;
+    Some(request)
+}
+
+/// 根据请求字节生成完整的响应字节（16 字节响应信息 + 有效数据）。
+///
+/// 响应信息中的序号和请求类型与请求相同；有效数据压缩之后长度变化时，发送压缩的数据。
+pub fn respond(request: &[u8]) -> Vec<u8> {
+    let cmd = u16_from_le_bytes(request, 10);
+    let body = body(cmd, request);
+    frame(request, &body)
+}
+
+/// 把有效数据封装成响应字节。
+pub fn frame(request: &[u8], body: &[u8]) -> Vec<u8> {
+    let zipped = miniz_oxide::deflate::compress_to_vec_zlib(body, 6);
+    let payload = if zipped.len() != body.len() {
+        &zipped[..]
+    } else {
+        body
+    };
+    let mut v = Vec::with_capacity(16 + payload.len());
+    v.extend([0xb1, 0xcb, 0x74, 0x00, request[0]]);
+    v.extend(&request[1..5]); // 序号
+    v.push(request[5]);
+    v.extend(&request[10..12]); // 请求类型
+    v.extend((payload.len() as u16).to_le_bytes());
+    v.extend((body.len() as u16).to_le_bytes());
+    v.extend(payload);
+    v
+}
+
+/// 请求类型对应的有效数据。
+fn body(cmd: u16, request: &[u8]) -> Vec<u8> {
+    let arg = |pos| u16_from_le_bytes(request, pos);
+    match cmd {
+        // 测试包
+        0x000d => vec![0; 1],
+        0x0fdb => vec![0; 32],
+        // SecurityCount
+        0x044e => match arg(12) {
+            0 => 13471u16,
+            _ => 18065,
+        }
+        .to_le_bytes()
+        .to_vec(),
+        // SecurityList
+        0x0450 => security_list(arg(14) as usize),
+        // Kline
+        0x052d => kline(
+            KlineCategory::try_from(arg(20)).unwrap_or_default(),
+            arg(24) as usize,
+            arg(26) as usize,
+        ),
+        // Xdxr
+        0x000f => {
+            let mut v = vec![0; 9];
+            v.extend(1u16.to_le_bytes());
+            v.extend(XDXR);
+            v
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn security_list(start: usize) -> Vec<u8> {
+    let list = SECURITIES.get(start..).unwrap_or_default();
+    let mut v = (list.len() as u16).to_le_bytes().to_vec();
+    for (code, name) in list {
+        v.extend(code.as_bytes());
+        v.extend(100u16.to_le_bytes());
+        let mut name = encoding_rs::GBK.encode(name).0.into_owned();
+        name.resize(8, 0);
+        v.extend(name);
+        v.extend([0; 4]);
+        v.push(2);
+        v.extend([0; 8]);
+    }
+    v
+}
+
+/// 从最新一根 K 线往前数 start 根开始，取 count 根，按时间顺序编码。
+fn kline(category: KlineCategory, start: usize, count: usize) -> Vec<u8> {
+    let end = BARS.len().saturating_sub(start);
+    let bars = &BARS[end.saturating_sub(count)..end];
+    let mut v = (bars.len() as u16).to_le_bytes().to_vec();
+    let mut base = 0;
+    for &(date, open, close, high, low) in bars {
+        if category.has_time() {
+            let (year, md) = (date / 10000, date % 10000);
+            v.extend((((year - 2004) << 11) as u16 + md as u16).to_le_bytes());
+            v.extend((15u16 * 60).to_le_bytes());
+        } else {
+            v.extend(date.to_le_bytes());
+        }
+        for p in [open - base, close - open, high - open, low - open] {
+            encode_price(p, &mut v);
+        }
+        v.extend(1235775464u32.to_le_bytes());
+        v.extend(1326643033u32.to_le_bytes());
+        base = close;
+    }
+    v
+}
+
+/// [`price`][crate::tcp::helper::price] 的逆过程。
+pub fn encode_price(p: i32, v: &mut Vec<u8>) {
+    let mut n = p.unsigned_abs();
+    let mut byte = (n & 0x3f) as u8 | if p < 0 { 0x40 } else { 0 };
+    n >>= 6;
+    while n > 0 {
+        v.push(byte | 0x80);
+        byte = (n & 0x7f) as u8;
+        n >>= 7;
+    }
+    v.push(byte);
+}
+
+#[test]
+fn price_roundtrip() {
+    for p in [0, 1, -1, 63, -64, 220, -420, 18100, -450, 123456789] {
+        let mut v = Vec::new();
+        encode_price(p, &mut v);
+        assert_eq!(crate::tcp::helper::price(&v, &mut 0).unwrap(), p, "{v:?}");
+    }
+    let mut v = Vec::new();
+    encode_price(18100, &mut v);
+    assert_eq!(v, [180, 154, 2]);
+}
+
+#[test]
+fn offline() -> super::Result<()> {
+    use crate::tcp::{stock::*, KlineCategory, Market, SecurityCount, SecurityList, Tdx};
+
+    let server = MockServer::start()?;
+    let mut tcp = server.connect()?;
+
+    assert_eq!(
+        *SecurityCount::new(Market::SZ).recv_parsed(&mut tcp)?,
+        13471
+    );
+    assert_eq!(
+        *SecurityCount::new(Market::SH).recv_parsed(&mut tcp)?,
+        18065
+    );
+
+    let list = SecurityList::new(Market::SZ, 1)
+        .recv_parsed(&mut tcp)?
+        .to_vec();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].code, "000002");
+    assert_eq!(list[1].name, "深证成指");
+
+    let mut kline = Kline::default();
+    let data = kline.recv_parsed(&mut tcp)?;
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[0].dt.clone().to_u32(), 20210923);
+    assert_eq!((data[0].open, data[0].close), (17.99, 18.41));
+    assert_eq!((data[2].high, data[2].low), (18.6, 18.1));
+    assert_eq!(data[2].vol, 1379837.0);
+    assert_eq!(data[2].amount, 2465683712.0);
+    let all = kline.recv_all(&mut tcp)?;
+    assert_eq!(all.len(), BARS.len());
+    assert_eq!(all[0].close, 18.22);
+
+    let minute = Kline::new(Market::SZ, "000001", KlineCategory::ExMin1, 0, 1)
+        .recv_parsed(&mut tcp)?
+        .to_vec();
+    assert_eq!((minute[0].dt.day, minute[0].dt.hour), (27, 15));
+
+    let xdxr = Xdxr::default().recv_parsed(&mut tcp)?.to_vec();
+    assert_eq!(xdxr.len(), 1);
+    assert_eq!((xdxr[0].date, xdxr[0].pg_hzgb), (19900301, 1.0));
+
+    // 不支持的请求得到空的有效数据
+    assert!(Transaction::default().recv(&mut tcp)?.is_empty());
+
+    // 请求和响应都不借用其他数据，可以发送到其他线程
+    let req = std::thread::spawn(|| {
+        KlineRequest::new(Market::SZ, String::from("000001"), KlineCategory::Day, 0, 2)
+    })
+    .join()
+    .unwrap();
+    let bars = tcp.request(&req)?;
+    let bars = std::thread::spawn(move || bars).join().unwrap();
+    assert_eq!((&*bars[1].code, bars[1].close), ("000001", 18.53));
+    Ok(())
+}
@@ -1,7 +1,7 @@
-use crate::bytes_helper::u16_from_le_bytes;
 use log::trace;
 use std::{
-    io::{BufReader, Read, Result, Write},
+    fs::File,
+    io::{BufReader, Read, Write},
     net::{SocketAddr, TcpStream},
     time::Duration,
 };
@@ -10,14 +10,41 @@ use std::{
 #[cfg(test)]
 pub(crate) mod tests;
 
+mod error;
+pub use error::{ensure_len, Error, Result};
+
+mod header;
+pub use header::{request_seq, with_seq, ResponseHeader};
+
 mod basic;
 pub use basic::*;
 
+mod builder;
+pub use builder::TcpBuilder;
+
+mod batch;
+pub use batch::BATCH_SIZE;
+
+mod market;
+pub use market::{KlineCategory, Market};
+
 pub mod helper;
 pub mod ip;
+pub mod pool;
+
+pub mod client;
+pub mod record;
+
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
 pub mod stock;
 
+pub mod exhq;
+
 /// 用于缓冲读取 TcpStream 的数据。
 pub type BufTcp = BufReader<TcpStream>;
 
@@ -27,31 +54,40 @@ pub struct Tcp {
     stream: TcpStream,
     buffer: BufTcp,
     recv: [u8; RECV_SIZE],
+    /// 上一次请求的序号
+    seq: u32,
+    /// 录制的文件，见 [`Tcp::record`]
+    record: Option<File>,
+    /// 见 [`Tcp::is_broken`]
+    broken: bool,
 }
 
 impl Tcp {
-    /// 已发送三个测试包
+    /// 连接最快的服务器（见 [`tcpstream`]），已发送三个测试包。
+    ///
+    /// 第一次调用会阻塞到所有服务器测速完毕，见 [`ip::fastest_stock_ips`] 。
     pub fn new() -> Result<Self> {
         let (stream, buffer, recv) = tcpstream()?;
         let mut tcp = Self {
             stream,
             buffer,
             recv,
+            seq: 0,
+            record: None,
+            broken: false,
         };
         send_packs(&mut tcp, false)?;
         Ok(tcp)
     }
 
-    /// 已发送三个测试包
+    /// 已发送三个测试包。需要修改连接选项时，使用 [`TcpBuilder`] 。
     pub fn new_with_ip(ip: &SocketAddr) -> Result<Self> {
-        let (stream, buffer, recv) = tcpstream_ip(ip)?;
-        let mut tcp = Self {
-            stream,
-            buffer,
-            recv,
-        };
-        send_packs(&mut tcp, false)?;
-        Ok(tcp)
+        TcpBuilder::new().connect(ip)
+    }
+
+    /// 连接选项，见 [`TcpBuilder`] 。
+    pub fn builder() -> TcpBuilder {
+        TcpBuilder::new()
     }
 
     /// 发送并接收字节。需要对接收的字节进行解析（参考 [`Tdx::parse`] 的实现）。
@@ -64,6 +100,19 @@ impl Tcp {
         Ok((self.stream.write(send)?, self.buffer.read(&mut self.recv)?))
     }
 
+    /// 把之后每次请求的字节和响应的原始字节（含 16 字节响应信息，未解压）依次写入文件，
+    /// 用 [`record::Replay`] 回放。文件已存在时会被覆盖。
+    pub fn record(&mut self, path: impl AsRef<std::path::Path>) -> Result<&mut Self> {
+        self.record = Some(File::create(path)?);
+        Ok(self)
+    }
+
+    /// 停止录制。
+    pub fn stop_recording(&mut self) -> &mut Self {
+        self.record = None;
+        self
+    }
+
     pub fn into_inner(self) -> (TcpStream, BufTcp, [u8; RECV_SIZE]) {
         (self.stream, self.buffer, self.recv)
     }
@@ -75,6 +124,68 @@ impl Tcp {
     pub fn get_ref_recv(&self) -> &[u8] {
         &self.recv
     }
+
+    /// 上一次请求的序号。每次调用 [`send_recv`] 时，序号加 1 并写入请求字节。
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// 之前是否有请求出错（网络错误或者响应有误）。
+    ///
+    /// 出错之后，连接中可能残留未读取的响应，之后的请求会读到错位的响应，所以应重新连接。
+    /// [`Error::Parse`] 发生在读取完整的响应之后，不会使连接出错。
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// 记录请求是否出错，见 [`Tcp::is_broken`] 。
+    fn track<T>(&mut self, res: Result<T>) -> Result<T> {
+        self.broken |= res.is_err();
+        res
+    }
+
+    /// 上一次响应的响应信息。
+    pub fn header(&self) -> Result<ResponseHeader> {
+        ResponseHeader::parse(&self.recv)
+    }
+
+    /// 录制时，写入请求字节、当前的响应信息和未解压的有效数据。
+    fn write_record(&mut self, send: &[u8], buf: &[u8]) -> Result<()> {
+        if let Some(file) = &mut self.record {
+            file.write_all(&[send, &self.recv, buf].concat())?;
+        }
+        Ok(())
+    }
+
+    /// 发送请求，返回解析后的响应。见 [`Request`] 。
+    pub fn request<R: Request>(&mut self, req: &R) -> Result<R::Response> {
+        let response = send_recv_decompress(self, &req.send(), R::TAG)?;
+        req.decode(&response)
+    }
+
+    /// 复制请求字节，并写入下一个序号。
+    fn next_request(&mut self, send: &[u8]) -> Vec<u8> {
+        self.seq = self.seq.wrapping_add(1);
+        with_seq(send, self.seq)
+    }
+}
+
+/// 发送请求字节并得到响应的有效数据（已解压）的方式。
+///
+/// [`Tcp`] 通过网络请求；[`record::Replay`] 回放录制的会话。[`Tdx`] 的方法和需要多次请求的方法
+/// （比如 [`stock::Kline::recv_all`]、[`stock::ReportFile::download`]、[`stock::BlockInfo::recv_all`]）
+/// 都接受任意的 [`Transport`] ，所以录制的真实会话可以直接用作回归测试。
+pub trait Transport {
+    /// 发送请求的字节（序号由实现者写入），返回响应的有效数据（已解压）。
+    /// tag 为请求的用途（[`Tdx::TAG`]），用于日志和错误信息。
+    fn exchange(&mut self, send: &[u8], tag: &'static str) -> Result<Vec<u8>>;
+}
+
+/// 见 [`send_recv_decompress`] 。
+impl Transport for Tcp {
+    fn exchange(&mut self, send: &[u8], tag: &'static str) -> Result<Vec<u8>> {
+        send_recv_decompress(self, send, tag)
+    }
 }
 
 pub trait Tdx {
@@ -87,8 +198,7 @@ pub trait Tdx {
     /// 比如请求日线时发送字节在当天收盘后是不变的，次日交易日请求得到的数据则可能改变。
     ///
     /// 如果发送请求的字节有误，则无法得到响应
-    /// （比如设置了读取超时，无响应情况下会得到：
-    /// [`WouldBlock` error][`std::io::ErrorKind::WouldBlock`]）。
+    /// （比如设置了读取超时，无响应情况下会得到 [`Error::Timeout`]）。
     ///
     /// 字节具体的含义见 Implementor 的 Tdx trait 部分的 `SEND` 文档。
     const SEND: &'static [u8];
@@ -99,20 +209,20 @@ pub trait Tdx {
     type Item: ?Sized;
 
     /// 真正发送的字节。
-    fn send(&mut self) -> &[u8];
+    fn send(&self) -> &[u8];
 
     /// 得到响应的字节。响应的字节长度无法预测。
-    fn recv(&mut self, tcp: &mut Tcp) -> Result<Vec<u8>> {
-        send_recv_decompress(tcp, self.send(), Self::TAG)
+    fn recv(&mut self, tcp: &mut (impl Transport + ?Sized)) -> Result<Vec<u8>> {
+        tcp.exchange(self.send(), Self::TAG)
     }
 
-    /// 解析响应的字节。
-    fn parse(&mut self, response: Vec<u8>);
+    /// 解析响应的字节。有效数据与请求的格式不符时返回 [`Error::Parse`] 。
+    fn parse(&mut self, response: Vec<u8>) -> Result<()>;
 
     /// 得到和解析响应的字节，并返回解析的数据。
-    fn recv_parsed(&mut self, tcp: &mut Tcp) -> Result<&Self::Item> {
+    fn recv_parsed(&mut self, tcp: &mut (impl Transport + ?Sized)) -> Result<&Self::Item> {
         let response = self.recv(tcp)?;
-        self.parse(response);
+        self.parse(response)?;
         Ok(self.result())
     }
 
@@ -120,23 +230,66 @@ pub trait Tdx {
     fn result(&self) -> &Self::Item;
 }
 
+/// 请求与响应分离的接口。
+///
+/// [`Tdx`] 把请求的字节、响应的字节和解析的数据保存在同一个结构体中，解析的数据借用这个结构体；
+/// 而 [`Request`] 的实现者只保存请求的参数（owned ，比如证券代码为 `String`），
+/// [`Request::send`] 生成请求的字节，[`Request::decode`] 返回 owned 的响应。
+/// 请求和响应都与其他数据没有借用关系，所以可以发送到其他线程、保存下来或者序列化。
+///
+/// 本库中每个 [`Tdx`] 结构体都有对应的 `*Request` 类型（比如 [`stock::Kline`] 对应
+/// [`stock::KlineRequest`]），结构体的 `request` 方法得到对应的请求；
+/// [`Tdx::parse`] 只是调用对应请求的 [`Request::decode`] ，并把结果保存在结构体中。
+///
+/// ```rust,no_run
+/// use rustdx::tcp::{stock::KlineRequest, KlineCategory, Market, Tcp};
+///
+/// let req = KlineRequest::new(Market::SZ, "000001", KlineCategory::Day, 0, 3);
+/// let handle = std::thread::spawn(move || -> rustdx::tcp::Result<_> {
+///     Tcp::new()?.request(&req)
+/// });
+/// println!("{:?}", handle.join().unwrap()?);
+/// # Ok::<(), rustdx::tcp::Error>(())
+/// ```
+pub trait Request {
+    /// 描述此次请求的用途，同 [`Tdx::TAG`] 。
+    const TAG: &'static str;
+    /// 解析后的响应。
+    type Response: serde::Serialize + Send + 'static;
+
+    /// 请求的字节。
+    fn send(&self) -> Box<[u8]>;
+
+    /// 解析响应的字节（已解压的有效数据）。有效数据与请求的格式不符时返回 [`Error::Parse`] 。
+    fn decode(&self, response: &[u8]) -> Result<Self::Response>;
+}
+
 /// 此函数完成以下事情：
-/// 1. 接收响应的字节，并且在 debug 模式下验证有效数据的长度
+/// 1. 接收响应的字节，并且验证响应信息与请求对应、有效数据的长度
 /// 2. 根据响应信息的解压前后长度，进行数据解压
 /// 3. 消耗缓冲区的字节（否则下次 read 的内容是上次未读/未消耗的字节），返回有效数据
 ///
 /// 有效数据：包含实际有用信息的数据。
 pub fn send_recv_decompress(tcp: &mut Tcp, send: &[u8], tag: &str) -> Result<Vec<u8>> {
-    let (mut buf, deflate_size, inflate_size) = send_recv(tcp, send, tag)?;
+    let (header, buf) = send_recv(tcp, send, tag)?;
+    let res = decompress(buf, &header);
+    tcp.track(res)
+}
 
-    if deflate_size != inflate_size {
-        buf = miniz_oxide::inflate::decompress_to_vec_zlib(&buf).unwrap();
+/// 根据响应信息的解压前后长度，进行数据解压。
+pub(crate) fn decompress(mut buf: Vec<u8>, header: &ResponseHeader) -> Result<Vec<u8>> {
+    if header.is_compressed() {
+        buf = miniz_oxide::inflate::decompress_to_vec_zlib(&buf).map_err(Error::Decompress)?;
         trace!("解压后数据：\n{:?}\n", buf);
-        debug_assert_eq!(buf.len(), inflate_size as usize);
+        if buf.len() != header.inflate_size as usize {
+            return Err(Error::SizeMismatch {
+                expected: header.inflate_size as usize,
+                found: buf.len(),
+            });
+        }
     } else {
         trace!("无需解压\n");
     };
-
     Ok(buf)
 }
 
@@ -145,37 +298,80 @@ pub fn send_recv_decompress(tcp: &mut Tcp, send: &[u8], tag: &str) -> Result<Vec
 // 否则下次 read 的内容是上次的未读字节。
 // 如果不使用 BufReader ，那么需要手动 read 剩余字节。
 // 对于 TcpStream ，Write::flush 没有做任何事情，所以无需调用。
-pub fn send_recv(tcp: &mut Tcp, send: &[u8], tag: &str) -> Result<(Vec<u8>, u16, u16)> {
-    tcp.send_recv(send)?;
-    trace!("{}\nsend: {:?}\nrecv[16B]: {:?}", tag, send, tcp);
+//
+// 发送的请求字节会被写入递增的序号（见 [`Tcp::seq`]），响应信息中的序号和请求类型
+// 必须与之相同，否则说明读到的是其他请求的响应。
+//
+// 出错时，连接被标记为出错，见 [`Tcp::is_broken`] 。
+pub fn send_recv(tcp: &mut Tcp, send: &[u8], tag: &str) -> Result<(ResponseHeader, Vec<u8>)> {
+    let res = send_recv_frame(tcp, send, tag);
+    tcp.track(res)
+}
+
+fn send_recv_frame(tcp: &mut Tcp, send: &[u8], tag: &str) -> Result<(ResponseHeader, Vec<u8>)> {
+    let send = &tcp.next_request(send)[..];
+    let (_, read) = tcp.send_recv(send)?;
+    trace!("{}\nsend: {:?}", tag, send);
+    let (header, buf) = read_frame(tcp, read, tag)?;
+    tcp.write_record(send, &buf)?;
+
+    // 已读完整个响应，所以即使响应与请求不对应，缓冲区中也不会残留这个响应的字节
+    header.check(send)?;
+    Ok((header, buf))
+}
+
+/// 读取一个完整的响应：16 字节的响应信息（已读取 read 字节）和未解压的有效数据。
+fn read_frame(tcp: &mut Tcp, mut read: usize, tag: &str) -> Result<(ResponseHeader, Vec<u8>)> {
+    // 响应信息可能分多次到达
+    while read < RECV_SIZE {
+        match tcp.buffer.read(&mut tcp.recv[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    trace!("{}\nrecv[16B]: {:?}", tag, tcp);
+    if read < RECV_SIZE {
+        return Err(Error::ShortFrame {
+            expected: RECV_SIZE,
+            found: read,
+        });
+    }
+    let header = tcp.header()?;
 
-    let deflate_size = u16_from_le_bytes(&tcp.recv, 12); // 响应信息中的待解压长度
-    let mut buf = vec![0; deflate_size as usize];
-    tcp.buffer.read_exact(&mut buf)?;
+    let deflate_size = header.deflate_size; // 响应信息中的待解压长度
+    let mut buf = Vec::with_capacity(deflate_size as usize);
+    (&mut tcp.buffer)
+        .take(deflate_size as u64)
+        .read_to_end(&mut buf)?;
+    if buf.len() < deflate_size as usize {
+        return Err(Error::ShortFrame {
+            expected: RECV_SIZE + deflate_size as usize,
+            found: RECV_SIZE + buf.len(),
+        });
+    }
 
-    let inflate_size = u16_from_le_bytes(&tcp.recv, 14); // 响应信息中的解压后长度
+    let inflate_size = header.inflate_size; // 响应信息中的解压后长度
     #[rustfmt::skip]
     trace!("\n解压前：#{:?}# -> {}，解压后：#{:?}# -> {}\n剩余数据（即解压前）：{:x?}\n",
            &tcp.recv[12..14], deflate_size, &tcp.recv[14..16], inflate_size, buf);
-
-    Ok((buf, deflate_size, inflate_size))
+    Ok((header, buf))
 }
 
 /// 默认的超时值。
 pub const TIMEOUT: Duration = Duration::from_millis(100);
 
 /// 快速引入 tcpstream，设置 100 毫秒超时。
-/// TODO: 固定？随机？取最快的 ip？
+///
+/// 按照 [`ip::fastest_stock_ips`] 的顺序连接，返回第一个成功的连接；
+/// 没有可用的测速结果时（包括测速失败之后的 [`ip::PROBE_COOLDOWN`] 内），连接 `STOCK_IP[0]` 。
 pub fn tcpstream() -> Result<(TcpStream, BufTcp, [u8; RECV_SIZE])> {
-    tcpstream_ip(&ip::STOCK_IP[0])
+    ip::fastest_stock_ips()
+        .iter()
+        .find_map(|l| tcpstream_ip(&l.addr).ok())
+        .map_or_else(|| tcpstream_ip(&ip::STOCK_IP[0]), Ok)
 }
 
-/// 快速引入 tcpstream，设置 100 毫秒超时。
+/// 快速引入 tcpstream，设置 100 毫秒超时。其他选项见 [`TcpBuilder::tcpstream`] 。
 pub fn tcpstream_ip(ip: &SocketAddr) -> Result<(TcpStream, BufTcp, [u8; RECV_SIZE])> {
-    let stream = TcpStream::connect_timeout(ip, TIMEOUT)?;
-    stream.set_read_timeout(Some(TIMEOUT))?;
-    stream.set_write_timeout(Some(TIMEOUT))?;
-    let recv = [0; RECV_SIZE];
-    let buffer = BufReader::new(stream.try_clone()?);
-    Ok((stream, buffer, recv))
+    TcpBuilder::new().tcpstream(ip)
 }
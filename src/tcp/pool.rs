@@ -0,0 +1,343 @@
+//! 连接池：在多个服务器上保持若干条已发送测试包的 [`Tcp`] 连接。
+//!
+//! ```rust,no_run
+//! use rustdx::tcp::{pool::Pool, stock::Kline, Tdx};
+//!
+//! let pool = Pool::new(4)?;
+//! let mut kline = Kline::default();
+//! let data = pool.recv_parsed(&mut kline)?;
+//!
+//! // 也可以取出一条连接，连续发送多个请求；离开作用域后连接自动归还
+//! let mut tcp = pool.get()?;
+//! kline.recv_parsed(&mut tcp)?;
+//! # Ok::<(), rustdx::tcp::Error>(())
+//! ```
+//!
+//! 连接或请求失败的服务器会被标记为不健康，在 [`Pool::COOLDOWN`] 时间内不再使用；
+//! 失败的连接（见 [`Tcp::is_broken`]）在归还时会被丢弃，之后按需在其他服务器上新建连接来补充。
+
+use super::{
+    ip::STOCK_IP, send_recv_decompress, Error, Heartbeat, Market, Request, Result, Tcp, Tdx,
+    Transport,
+};
+use std::{
+    net::SocketAddr,
+    ops::{Deref, DerefMut},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// 连接池。可在多线程之间共享（比如放在 `Arc` 中）。
+#[derive(Debug)]
+pub struct Pool {
+    size: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    servers: Vec<Server>,
+    /// 空闲的连接及其服务器在 servers 中的位置
+    idle: Vec<(usize, Tcp)>,
+    /// 下次新建连接时，首先尝试的服务器位置
+    next: usize,
+}
+
+/// 服务器及其健康状态。
+#[derive(Debug, Clone)]
+pub struct Server {
+    pub addr: SocketAddr,
+    /// 最近一次失败的时间，None 表示健康
+    pub failed_at: Option<Instant>,
+    /// 累计失败次数
+    pub failures: u32,
+}
+
+impl Server {
+    fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            failed_at: None,
+            failures: 0,
+        }
+    }
+
+    /// 最近没有失败过。
+    pub fn is_healthy(&self) -> bool {
+        self.failed_at.is_none()
+    }
+
+    /// 健康，或者距离上次失败已经超过 [`Pool::COOLDOWN`] 。
+    fn is_usable(&self) -> bool {
+        self.failed_at.is_none_or(|t| t.elapsed() >= Pool::COOLDOWN)
+    }
+
+    fn mark_failed(&mut self) {
+        self.failed_at = Some(Instant::now());
+        self.failures += 1;
+    }
+}
+
+impl Pool {
+    /// 不健康的服务器在这段时间之后才会被再次尝试。
+    pub const COOLDOWN: Duration = Duration::from_secs(60);
+
+    /// 在 [`STOCK_IP`] 上建立 size 条连接。
+    pub fn new(size: usize) -> Result<Self> {
+        Self::with_ips(&*STOCK_IP, size)
+    }
+
+    /// 在给定的服务器上轮流建立 size 条连接。
+    ///
+    /// 只要有一条连接建立成功，就返回连接池；否则返回最后一次连接的错误。
+    pub fn with_ips(ips: &[SocketAddr], size: usize) -> Result<Self> {
+        let pool = Self {
+            size,
+            inner: Mutex::new(Inner {
+                servers: ips.iter().copied().map(Server::new).collect(),
+                idle: Vec::with_capacity(size),
+                next: 0,
+            }),
+        };
+        let mut err = None;
+        for _ in 0..size {
+            match pool.connect() {
+                Ok((server, tcp)) => pool.lock().idle.push((server, tcp)),
+                Err(e) => err = Some(e),
+            }
+        }
+        match err {
+            Some(e) if pool.idle_len() == 0 => Err(e),
+            _ => Ok(pool),
+        }
+    }
+
+    /// 连接池保持的连接数量。
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 当前空闲的连接数量。
+    pub fn idle_len(&self) -> usize {
+        self.lock().idle.len()
+    }
+
+    /// 所有服务器的健康状态。
+    pub fn servers(&self) -> Vec<Server> {
+        self.lock().servers.clone()
+    }
+
+    /// 取出一条已发送测试包的连接。没有空闲连接时，在下一个可用的服务器上新建连接。
+    pub fn get(&self) -> Result<PooledTcp<'_>> {
+        let idle = self.lock().idle.pop();
+        let (server, tcp) = match idle {
+            Some(conn) => conn,
+            None => self.connect()?,
+        };
+        Ok(PooledTcp {
+            pool: self,
+            server,
+            tcp: Some(tcp),
+        })
+    }
+
+    /// 取出一条连接，完成请求。请求失败时，该连接被丢弃，且其服务器被标记为不健康。
+    pub fn recv_parsed<'t, T: Tdx>(&self, tdx: &'t mut T) -> Result<&'t T::Item> {
+        let mut tcp = self.get()?;
+        let response = tdx.recv(&mut tcp)?;
+        drop(tcp);
+        tdx.parse(response)?;
+        Ok(tdx.result())
+    }
+
+    /// 同 [`Pool::recv_parsed`] ，返回 owned 的响应，见 [`Request`] 。
+    pub fn request<R: Request>(&self, req: &R) -> Result<R::Response> {
+        let mut tcp = self.get()?;
+        let response = send_recv_decompress(&mut tcp, &req.send(), R::TAG)?;
+        drop(tcp);
+        req.decode(&response)
+    }
+
+    /// 健康检查：对每条空闲连接发送心跳包，丢弃失败的连接，并新建连接补足 size 条。
+    ///
+    /// 返回补足之后的空闲连接数量。
+    pub fn check(&self) -> usize {
+        let idle = std::mem::take(&mut self.lock().idle);
+        for (server, mut tcp) in idle {
+            match Heartbeat::new(Market::SZ).recv(&mut tcp) {
+                Ok(_) => self.lock().idle.push((server, tcp)),
+                Err(_) => self.lock().servers[server].mark_failed(),
+            }
+        }
+        while self.idle_len() < self.size {
+            match self.connect() {
+                Ok(conn) => self.lock().idle.push(conn),
+                Err(_) => break,
+            }
+        }
+        self.idle_len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // 持有锁时不会 panic，所以忽略中毒的情况
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 从 next 开始轮流尝试可用的服务器，直到连接成功。连接时不持有锁。
+    fn connect(&self) -> Result<(usize, Tcp)> {
+        let candidates: Vec<(usize, SocketAddr)> = {
+            let mut inner = self.lock();
+            let n = inner.servers.len();
+            let start = inner.next;
+            inner.next = (start + 1) % n.max(1);
+            (0..n)
+                .map(|i| (start + i) % n)
+                .filter(|&i| inner.servers[i].is_usable())
+                .map(|i| (i, inner.servers[i].addr))
+                .collect()
+        };
+
+        let mut err = None;
+        for (server, addr) in candidates {
+            match Tcp::new_with_ip(&addr) {
+                Ok(tcp) => {
+                    self.lock().servers[server].failed_at = None;
+                    return Ok((server, tcp));
+                }
+                Err(e) => {
+                    self.lock().servers[server].mark_failed();
+                    err = Some(e);
+                }
+            }
+        }
+        Err(err.unwrap_or(Error::NoServer))
+    }
+
+    /// 归还连接。空闲连接已满时，直接关闭该连接。
+    fn put(&self, server: usize, tcp: Tcp) {
+        let mut inner = self.lock();
+        if inner.idle.len() < self.size {
+            inner.idle.push((server, tcp));
+        }
+    }
+}
+
+/// 从 [`Pool`] 中取出的连接，可以像 [`Tcp`] 一样使用。离开作用域时自动归还；
+/// 如果请求出错（见 [`Tcp::is_broken`]），则丢弃该连接，并把服务器标记为不健康。
+#[derive(Debug)]
+pub struct PooledTcp<'p> {
+    pool: &'p Pool,
+    server: usize,
+    tcp: Option<Tcp>,
+}
+
+impl PooledTcp<'_> {
+    /// 连接的服务器地址。
+    pub fn addr(&self) -> SocketAddr {
+        self.pool.lock().servers[self.server].addr
+    }
+
+    /// 丢弃该连接，并把服务器标记为不健康。请求出错的连接会在离开作用域时自动丢弃，
+    /// 无需调用此方法。
+    pub fn invalidate(mut self) {
+        self.tcp = None;
+        self.pool.lock().servers[self.server].mark_failed();
+    }
+}
+
+impl Deref for PooledTcp<'_> {
+    type Target = Tcp;
+
+    fn deref(&self) -> &Tcp {
+        self.tcp.as_ref().expect("连接已被丢弃")
+    }
+}
+
+impl DerefMut for PooledTcp<'_> {
+    fn deref_mut(&mut self) -> &mut Tcp {
+        self.tcp.as_mut().expect("连接已被丢弃")
+    }
+}
+
+/// 同 [`Tcp`] 的 [`Transport`] 。
+impl Transport for PooledTcp<'_> {
+    fn exchange(&mut self, send: &[u8], tag: &'static str) -> Result<Vec<u8>> {
+        Tcp::exchange(self, send, tag)
+    }
+}
+
+impl Drop for PooledTcp<'_> {
+    fn drop(&mut self) {
+        match self.tcp.take() {
+            Some(tcp) if tcp.is_broken() => self.pool.lock().servers[self.server].mark_failed(),
+            Some(tcp) => self.pool.put(self.server, tcp),
+            None => (),
+        }
+    }
+}
+
+#[test]
+fn unreachable_servers() {
+    // 保留地址（RFC 5737），不会有服务器响应
+    let ips: [SocketAddr; 2] = [
+        "192.0.2.1:7709".parse().unwrap(),
+        "192.0.2.2:7709".parse().unwrap(),
+    ];
+    assert!(Pool::with_ips(&ips, 2).is_err());
+
+    let pool = Pool {
+        size: 2,
+        inner: Mutex::new(Inner {
+            servers: ips.iter().copied().map(Server::new).collect(),
+            idle: Vec::new(),
+            next: 0,
+        }),
+    };
+    pool.lock().servers.iter_mut().for_each(Server::mark_failed);
+    let err = pool.get().unwrap_err();
+    assert!(matches!(err, Error::NoServer));
+    assert!(pool
+        .servers()
+        .iter()
+        .all(|s| !s.is_healthy() && s.failures == 1));
+}
+
+#[test]
+fn broken_connection() -> Result<()> {
+    use std::net::Shutdown;
+
+    let server = super::mock::MockServer::start()?;
+    let pool = Pool::with_ips(&[server.addr()], 1)?;
+    {
+        let mut tcp = pool.get()?;
+        assert!(*Heartbeat::new(Market::SZ).recv_parsed(&mut tcp)? > 0);
+    }
+    assert_eq!(pool.idle_len(), 1);
+    {
+        // 请求出错之后，离开作用域的连接不会被归还
+        let mut tcp = pool.get()?;
+        tcp.get_ref().0.shutdown(Shutdown::Both)?;
+        assert!(Heartbeat::new(Market::SZ).recv_parsed(&mut tcp).is_err());
+        assert!(tcp.is_broken());
+    }
+    assert_eq!(pool.idle_len(), 0);
+    assert_eq!(pool.servers()[0].failures, 1);
+    Ok(())
+}
+
+#[test]
+fn connection() -> Result<()> {
+    let pool = Pool::new(2)?;
+    assert_eq!(pool.idle_len(), 2);
+    {
+        let (mut a, mut b) = (pool.get()?, pool.get()?);
+        assert_ne!(a.addr(), b.addr());
+        Heartbeat::new(Market::SZ).recv_parsed(&mut a)?;
+        Heartbeat::new(Market::SH).recv_parsed(&mut b)?;
+        assert_eq!(pool.idle_len(), 0);
+    }
+    assert_eq!(pool.idle_len(), 2);
+    assert!(*pool.recv_parsed(&mut Heartbeat::new(Market::SZ))? > 0);
+    assert_eq!(pool.check(), 2);
+    Ok(())
+}
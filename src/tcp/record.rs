@@ -0,0 +1,279 @@
+//! 录制和回放原始的请求、响应字节，用于把真实的会话保存成回归测试的数据。
+//!
+//! 录制：[`Tcp::record`][super::Tcp::record] 之后，每次请求都会向文件依次写入
+//! - 请求的字节：10 字节的请求信息 + 请求信息中记录长度的字节（2 字节请求类型 + 参数）；
+//! - 响应的字节：16 字节的响应信息 + 响应信息中记录长度的有效数据（未解压）。
+//!
+//! 所以录制的文件就是原始的 tcp 字节流，不需要额外的分隔符。
+//!
+//! 回放：[`Replay`] 读取录制的文件，对于同样的请求返回录制的响应，并交给 [`Tdx::parse`] 解析。
+//! [`Replay`] 与 [`Tcp`][super::Tcp] 一样实现了 [`Transport`] ，所以 [`Tdx`] 的方法和需要多次请求的方法
+//! （比如 [`Kline::recv_all`][super::stock::Kline::recv_all]）都可以直接回放：
+//! ```rust,no_run
+//! use rustdx::tcp::{record::Replay, stock::Kline, Tcp, Tdx};
+//!
+//! // 录制一次
+//! let mut tcp = Tcp::new()?;
+//! tcp.record("kline.bin")?;
+//! Kline::default().recv_parsed(&mut tcp)?;
+//! let all = Kline::default().recv_all(&mut tcp)?;
+//!
+//! // 离线回放
+//! let mut replay = Replay::open("kline.bin")?;
+//! let data = Kline::default().recv_parsed(&mut replay)?.to_vec();
+//! assert_eq!(data.len(), 3);
+//! assert_eq!(Kline::default().recv_all(&mut replay)?.len(), all.len());
+//! # Ok::<(), rustdx::tcp::Error>(())
+//! ```
+
+use super::{decompress, Error, Request, ResponseHeader, Result, Tdx, Transport, RECV_SIZE};
+use crate::bytes_helper::u16_from_le_bytes;
+use std::path::Path;
+
+/// 一次请求的原始字节和对应的原始响应字节。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub request: Vec<u8>,
+    /// 16 字节的响应信息 + 未解压的有效数据
+    pub response: Vec<u8>,
+}
+
+impl Frame {
+    /// 请求类型。请求的字节不足 12 字节时返回 None 。
+    pub fn cmd(&self) -> Option<u16> {
+        let cmd = self.request.get(10..12)?;
+        Some(u16_from_le_bytes(cmd, 0))
+    }
+
+    /// 响应信息。
+    pub fn header(&self) -> Result<ResponseHeader> {
+        let header = self.response.first_chunk().ok_or(Error::ShortFrame {
+            expected: RECV_SIZE,
+            found: self.response.len(),
+        })?;
+        ResponseHeader::parse(header)
+    }
+
+    /// 解压之后的有效数据。
+    pub fn body(&self) -> Result<Vec<u8>> {
+        let header = self.header()?;
+        decompress(self.response[RECV_SIZE..].to_vec(), &header)
+    }
+
+    /// 请求的字节是否相同。不比较请求信息中的序号（第 1 到 4 字节）。
+    pub fn matches(&self, request: &[u8]) -> bool {
+        self.request.len() == request.len()
+            && self.request.get(..1) == request.get(..1)
+            && self.request.get(5..) == request.get(5..)
+    }
+}
+
+/// 回放录制的会话。
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    frames: Vec<Frame>,
+    /// 已回放的帧
+    used: Vec<bool>,
+}
+
+impl Replay {
+    /// 读取 [`Tcp::record`][super::Tcp::record] 录制的文件。
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// 从录制的字节中依次解析请求和响应。字节不完整时返回 [`Error::ShortFrame`] 。
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self> {
+        let mut frames = Vec::new();
+        while !bytes.is_empty() {
+            let request = take(&mut bytes, 10, 6)?;
+            let response = take(&mut bytes, 16, 12)?;
+            frames.push(Frame { request, response });
+        }
+        Ok(Self {
+            used: vec![false; frames.len()],
+            frames,
+        })
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// 得到录制的响应（已解压）。
+    ///
+    /// 返回第一个未回放过、且请求字节相同的帧，所以同样的请求多次录制时，按录制的顺序回放。
+    /// 没有对应的帧时返回 [`Error::NotRecorded`] 。
+    pub fn recv<T: Tdx>(&mut self, tdx: &mut T) -> Result<Vec<u8>> {
+        self.replay(tdx.send(), T::TAG)
+    }
+
+    /// 同 [`Tcp::request`][super::Tcp::request] ：解析录制的响应，返回 owned 的响应。
+    pub fn request<R: Request>(&mut self, req: &R) -> Result<R::Response> {
+        let response = self.replay(&req.send(), R::TAG)?;
+        req.decode(&response)
+    }
+
+    /// 同 [`Transport::exchange`] 。
+    fn replay(&mut self, send: &[u8], tag: &'static str) -> Result<Vec<u8>> {
+        let pos = self
+            .frames
+            .iter()
+            .zip(&self.used)
+            .position(|(f, &used)| !used && f.matches(send))
+            .ok_or_else(|| Error::NotRecorded {
+                tag,
+                request: send.to_vec(),
+            })?;
+        self.used[pos] = true;
+        self.frames[pos].body()
+    }
+
+    /// 同 [`Tdx::recv_parsed`] ：得到和解析录制的响应，并返回解析的数据。
+    pub fn recv_parsed<'t, T: Tdx>(&mut self, tdx: &'t mut T) -> Result<&'t T::Item> {
+        let response = self.recv(tdx)?;
+        tdx.parse(response)?;
+        Ok(tdx.result())
+    }
+}
+
+/// 返回第一个未回放过、且请求字节相同的帧的有效数据，见 [`Replay::recv`] 。
+impl Transport for Replay {
+    fn exchange(&mut self, send: &[u8], tag: &'static str) -> Result<Vec<u8>> {
+        self.replay(send, tag)
+    }
+}
+
+/// 取出 head 字节的信息和信息中 pos 位置记录长度的字节。
+fn take(bytes: &mut &[u8], head: usize, pos: usize) -> Result<Vec<u8>> {
+    let len = if bytes.len() < head {
+        head
+    } else {
+        head + u16_from_le_bytes(bytes, pos) as usize
+    };
+    if bytes.len() < len {
+        return Err(Error::ShortFrame {
+            expected: len,
+            found: bytes.len(),
+        });
+    }
+    let (frame, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(frame.to_vec())
+}
+
+#[test]
+fn record_replay() -> Result<()> {
+    use super::{
+        mock::MockServer, stock::*, KlineCategory, Market, SecurityCount, SecurityCountRequest,
+    };
+
+    let path = std::env::temp_dir().join(format!("rustdx-record-{}.bin", std::process::id()));
+    let server = MockServer::start()?;
+    let mut tcp = server.connect()?;
+    tcp.record(&path)?;
+    let kline = Kline::default().recv_parsed(&mut tcp)?.to_vec();
+    let xdxr = Xdxr::default().recv_parsed(&mut tcp)?.to_vec();
+    assert_eq!(
+        *SecurityCount::new(Market::SZ).recv_parsed(&mut tcp)?,
+        13471
+    );
+    assert_eq!(
+        *SecurityCount::new(Market::SH).recv_parsed(&mut tcp)?,
+        18065
+    );
+    tcp.stop_recording();
+    Kline::default().recv_parsed(&mut tcp)?;
+    drop(server);
+
+    let mut replay = Replay::open(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(replay.frames().len(), 4);
+    assert_eq!(replay.frames()[0].cmd(), Some(0x052d));
+    let header = replay.frames()[0].header()?;
+    assert_eq!(header.cmd, 0x052d);
+    // 录制的是写入序号之后的请求
+    header.check(&replay.frames()[0].request)?;
+
+    // 不按录制的顺序请求
+    assert_eq!(
+        *replay.recv_parsed(&mut SecurityCount::new(Market::SH))?,
+        18065
+    );
+    let mut replayed = Xdxr::default();
+    let replayed = replay.recv_parsed(&mut replayed)?;
+    assert_eq!(format!("{replayed:?}"), format!("{xdxr:?}"));
+    let mut replayed = Kline::default();
+    let replayed = replay.recv_parsed(&mut replayed)?;
+    assert_eq!(format!("{replayed:?}"), format!("{kline:?}"));
+    let count = SecurityCountRequest::new(Market::SZ);
+    assert_eq!(replay.request(&count)?, 13471);
+    // 每帧只回放一次；未录制的请求
+    let not_found = |err| matches!(err, Error::NotRecorded { tag: "日线", .. });
+    assert!(not_found(replay.recv(&mut Kline::default()).unwrap_err()));
+    let mut kline = Kline::new(Market::SH, "600000", KlineCategory::Day, 0, 3);
+    assert!(not_found(replay.recv(&mut kline).unwrap_err()));
+    Ok(())
+}
+
+#[test]
+fn replay_paging() -> Result<()> {
+    use super::{mock::MockServer, stock::*, KlineCategory, Market};
+
+    let path = std::env::temp_dir().join(format!("rustdx-paging-{}.bin", std::process::id()));
+    let server = MockServer::start()?;
+    let mut tcp = server.connect()?;
+    tcp.record(&path)?;
+    // 从第 2 根开始往前：一页 3 根 K 线，之后一页为空
+    let mut kline = Kline::new(Market::SZ, "000001", KlineCategory::Day, 2, 3);
+    let all = kline.recv_all(&mut tcp)?;
+    assert_eq!(all.len(), 3);
+    drop(server);
+
+    let mut replay = Replay::open(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(replay.frames().len(), 2);
+    let replayed = kline.recv_all(&mut replay)?;
+    assert_eq!(format!("{replayed:?}"), format!("{all:?}"));
+    // 所有帧都已回放
+    assert!(matches!(
+        kline.recv_all(&mut replay),
+        Err(Error::NotRecorded { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn truncated() {
+    let request = super::SecurityCount::SEND;
+    let response = [
+        0xb1, 0xcb, 0x74, 0x00, 0x0c, 0, 0, 0, 0, 0, 0x4e, 0x04, 2, 0, 2, 0, 0x9f,
+    ];
+    let bytes = [request, &response].concat();
+    let err = Replay::from_bytes(&bytes).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ShortFrame {
+            expected: 18,
+            found: 17
+        }
+    ));
+    let replay = Replay::from_bytes(&[&bytes[..], &[0x34]].concat()).unwrap();
+    assert_eq!(replay.frames()[0].body().unwrap(), [0x9f, 0x34]);
+    // 手动构造的帧不完整时不会 panic
+    let frame = Frame {
+        request: request[..8].to_vec(),
+        response: Vec::new(),
+    };
+    assert_eq!(frame.cmd(), None);
+    assert!(frame.matches(&request[..8]) && !frame.matches(request));
+    assert!(matches!(frame.body(), Err(Error::ShortFrame { .. })));
+    let err = Replay::from_bytes(&bytes[..5]).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ShortFrame {
+            expected: 10,
+            found: 5
+        }
+    ));
+}
@@ -0,0 +1,300 @@
+use super::ReportFile;
+use crate::tcp::{ensure_len, helper::gbk, Request, Result, Tdx, Transport};
+
+/// 查询板块文件的元信息（文件大小和哈希值）。
+/// 对应于 pytdx 中的 hq.get_block_info_meta、GetBlockInfoMeta。
+#[derive(Debug, Clone)]
+pub struct BlockMeta<'d> {
+    pub send: Box<[u8]>,
+    /// 板块文件名，见 [`BlockInfo::ZS`] 等常量
+    pub filename: &'d str,
+    pub response: Vec<u8>,
+    pub data: BlockMetaData,
+}
+
+/// 默认查询 `block_zs.dat` 。
+impl<'d> Default for BlockMeta<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            filename: BlockInfo::ZS,
+            response: Vec::new(),
+            data: BlockMetaData::default(),
+        }
+    }
+}
+
+impl<'d> BlockMeta<'d> {
+    /// ## panic
+    /// 当 filename 的字节长度超过 40 时，程序会 panic。
+    pub fn new(filename: &'d str) -> Self {
+        let mut meta = Self::default();
+        meta.filename(filename);
+        meta
+    }
+
+    /// 修改文件名。
+    ///
+    /// ## panic
+    /// 当 filename 的字节长度超过 40 时，程序会 panic。
+    pub fn filename(&mut self, filename: &'d str) -> &mut Self {
+        self.filename = filename;
+        let arr = &mut self.send[12..52];
+        arr.fill(0);
+        arr[..filename.len()].copy_from_slice(filename.as_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> BlockMetaRequest {
+        BlockMetaRequest::new(self.filename)
+    }
+}
+
+impl<'a> Tdx for BlockMeta<'a> {
+    type Item = BlockMetaData;
+
+    /// `block_zs.dat` 元信息的请求字节。长度为 52。
+    /// ```python
+    /// struct.pack("<40s", filename) # 后 40 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x39, 0x18, 0x69, 0x00, 0x01, 0x2a, 0x00, 0x2a, 0x00, 0xc5, 0x02, 0x62, 0x6c, 0x6f,
+        0x63, 0x6b, 0x5f, 0x7a, 0x73, 0x2e, 0x64, 0x61, 0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    const TAG: &'static str = "板块文件信息";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`BlockMeta`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockMetaRequest {
+    pub filename: String,
+}
+
+impl BlockMetaRequest {
+    pub fn new(filename: impl Into<String>) -> Self {
+        Self {
+            filename: filename.into(),
+        }
+    }
+}
+
+impl Request for BlockMetaRequest {
+    const TAG: &'static str = <BlockMeta as Tdx>::TAG;
+    type Response = BlockMetaData;
+
+    /// ## panic
+    /// 当 filename 的字节长度超过 40 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; BlockMeta::LEN];
+        arr[..12].copy_from_slice(&BlockMeta::SEND[..12]);
+        arr[12..12 + self.filename.len()].copy_from_slice(self.filename.as_bytes());
+        arr.into()
+    }
+
+    /// ```python
+    /// (size, _, hash, _) = struct.unpack("<I1s32s1s", bytes)
+    /// ```
+    fn decode(&self, v: &[u8]) -> Result<BlockMetaData> {
+        ensure_len(Self::TAG, v, 37)?;
+        Ok(BlockMetaData {
+            size: crate::bytes_helper::u32_from_le_bytes(v, 0),
+            hash: String::from_utf8_lossy(&v[5..37]).into_owned(),
+        })
+    }
+}
+
+/// [`BlockMeta`] 的解析结果。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct BlockMetaData {
+    /// 文件大小
+    pub size: u32,
+    /// 文件内容的哈希值（32 位十六进制字符）
+    pub hash: String,
+}
+
+/// 板块成分股。对应于 pytdx 中的 hq.get_and_parse_block_info 。
+///
+/// 先使用 [`BlockMeta`] 查询文件大小，然后使用 [`ReportFile`] 分块下载整个文件，最后解析得到
+/// 每个板块（指数、概念、风格）包含的股票代码。
+///
+/// 已经下载好的板块文件（比如通达信安装目录下 `T0002/hq_cache/block_zs.dat`）可以直接使用
+/// [`BlockInfo::parse`] 解析。
+#[derive(Debug, Clone)]
+pub struct BlockInfo<'d> {
+    /// 板块文件名，见 [`BlockInfo::ZS`] 等常量
+    pub filename: &'d str,
+    /// 下载的文件内容
+    pub response: Vec<u8>,
+    pub data: Vec<BlockData>,
+}
+
+/// 默认为指数板块 `block_zs.dat` 。
+impl<'d> Default for BlockInfo<'d> {
+    fn default() -> Self {
+        Self::new(Self::ZS)
+    }
+}
+
+impl<'d> BlockInfo<'d> {
+    /// 指数板块
+    pub const ZS: &'static str = "block_zs.dat";
+    /// 概念板块
+    pub const GN: &'static str = "block_gn.dat";
+    /// 风格板块
+    pub const FG: &'static str = "block_fg.dat";
+    /// 解析失败时的错误信息
+    pub const TAG: &'static str = "板块文件";
+
+    pub fn new(filename: &'d str) -> Self {
+        Self {
+            filename,
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// 下载并解析整个板块文件。
+    pub fn recv_all(&mut self, tcp: &mut (impl Transport + ?Sized)) -> Result<&[BlockData]> {
+        let size = BlockMeta::new(self.filename).recv_parsed(tcp)?.size;
+        let mut file = Vec::with_capacity(size as usize);
+        ReportFile::new(self.filename)
+            .filesize(size)
+            .download(tcp, &mut file)?;
+        self.parse(file)?;
+        Ok(&self.data)
+    }
+
+    /// 解析板块文件：跳过 384 字节的文件头，之后 2 字节表示板块数量。
+    /// 每个板块占 2813 字节，使用 [`BlockData::parse`] 解析。
+    pub fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        ensure_len(Self::TAG, &v, 386)?;
+        let count = crate::bytes_helper::u16_from_le_bytes(&v, 384) as usize;
+        ensure_len(Self::TAG, &v, 386 + count * BlockData::LEN)?;
+        self.data = v[386..]
+            .chunks_exact(BlockData::LEN)
+            .take(count)
+            .map(BlockData::parse)
+            .collect();
+        self.response = v;
+        Ok(())
+    }
+
+    /// 板块名称对应的成分股代码。
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.data
+            .iter()
+            .find(|b| b.name == name)
+            .map(|b| b.codes.as_slice())
+    }
+}
+
+/// 一个板块及其成分股。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct BlockData {
+    /// 板块名称，比如 “沪深300”
+    pub name: String,
+    pub block_type: u16,
+    /// 成分股代码（不带市场前缀），比如 `600000`
+    pub codes: Vec<String>,
+}
+
+impl BlockData {
+    /// 一个板块在文件中占的字节数：9 字节名称、2 字节股票数量、2 字节类型、最多 400 个股票代码。
+    pub const LEN: usize = 13 + 2800;
+
+    /// 解析一个板块。传入最多 [`BlockData::LEN`] 字节的序列。
+    /// ```python
+    /// (name, stock_count, block_type) = struct.unpack("<9sHH", bytes[:13])
+    /// ```
+    /// 之后每 7 字节为一个以 `\0` 结尾的股票代码。
+    pub fn parse(bytes: &[u8]) -> Self {
+        use crate::bytes_helper::u16_from_le_bytes;
+        let count = u16_from_le_bytes(bytes, 9) as usize;
+        Self {
+            name: gbk(&bytes[..9]),
+            block_type: u16_from_le_bytes(bytes, 11),
+            codes: bytes[13..].chunks(7).take(count).map(gbk).collect(),
+        }
+    }
+}
+
+#[test]
+fn new_modify() {
+    let meta = BlockMeta::new(BlockInfo::ZS);
+    let mut meta2 = BlockMeta::new("incon.dat");
+    crate::tcp::tests::same_request(&meta2, &meta2.request());
+    meta2.filename(BlockInfo::ZS);
+    compare!(BlockMeta::default(), meta, meta2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(BlockMeta::default())?;
+    let mut tcp = crate::tcp::Tcp::new()?;
+    let mut block = BlockInfo::default();
+    let data = block.recv_all(&mut tcp)?;
+    assert!(!data.is_empty());
+    println!("{:?}", &data[..1]);
+    Ok(())
+}
+
+#[test]
+fn parse() {
+    let mut meta = BlockMeta::default();
+    let mut arr = 181046u32.to_le_bytes().to_vec();
+    arr.push(0);
+    arr.extend(b"0123456789abcdef0123456789abcdef");
+    arr.push(0);
+    meta.parse(arr).unwrap();
+    assert_eq!(meta.data.size, 181046);
+    assert_eq!(meta.data.hash, "0123456789abcdef0123456789abcdef");
+
+    let block = |name: &str, block_type: u16, codes: &[&str]| {
+        let mut bytes = encoding_rs::GBK.encode(name).0.into_owned();
+        bytes.resize(9, 0);
+        bytes.extend((codes.len() as u16).to_le_bytes());
+        bytes.extend(block_type.to_le_bytes());
+        for code in codes {
+            bytes.extend(code.as_bytes());
+            bytes.push(0);
+        }
+        bytes.resize(BlockData::LEN, 0);
+        bytes
+    };
+    let mut arr = vec![0; 384];
+    arr.extend(2u16.to_le_bytes());
+    arr.extend(block("沪深300", 2, &["600000", "000001"]));
+    arr.extend(block("上证50", 2, &["600036"]));
+    let mut info = BlockInfo::default();
+    info.parse(arr).unwrap();
+
+    assert_eq!(info.data.len(), 2);
+    assert_eq!(info.data[0].block_type, 2);
+    assert_eq!(
+        info.get("沪深300"),
+        Some(&["600000".into(), "000001".into()][..])
+    );
+    assert_eq!(info.get("上证50"), Some(&["600036".into()][..]));
+    assert_eq!(info.get("创业板"), None);
+}
@@ -0,0 +1,438 @@
+use crate::tcp::{ensure_len, Market, Request, Result, Tdx};
+
+/// 查询公司信息（F10）的目录。对应于 pytdx 中的 hq.get_company_info_category、
+/// GetCompanyInfoCategory。
+///
+/// 得到的每个目录项可交给 [`CompanyContent`] 获取具体内容。
+#[derive(Debug, Clone)]
+pub struct CompanyCategory<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    pub response: Vec<u8>,
+    pub data: Vec<CompanyCategoryData>,
+}
+
+/// 默认查询 #sz000001# 。
+impl<'d> Default for CompanyCategory<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<'d> CompanyCategory<'d> {
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str) -> Self {
+        let mut category = Self::default();
+        category.market(market).code(code);
+        category
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[12..14].copy_from_slice(&market.code().to_le_bytes());
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[14..20].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> CompanyCategoryRequest {
+        CompanyCategoryRequest::new(self.market, self.code)
+    }
+}
+
+impl<'a> Tdx for CompanyCategory<'a> {
+    type Item = [CompanyCategoryData];
+
+    /// #sz000001# F10 目录的请求字节。长度为 24。
+    /// ```python
+    /// struct.pack("<H6sI", market, code, 0) # 后 12 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x0f, 0x10, 0x9b, 0x00, 0x01, 0x0e, 0x00, 0x0e, 0x00, 0xcf, 0x02, 0x00, 0x00, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x00, 0x00, 0x00,
+    ];
+    const TAG: &'static str = "公司信息目录";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`CompanyCategory`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompanyCategoryRequest {
+    pub market: Market,
+    pub code: String,
+}
+
+impl CompanyCategoryRequest {
+    pub fn new(market: Market, code: impl Into<String>) -> Self {
+        Self {
+            market,
+            code: code.into(),
+        }
+    }
+}
+
+impl Request for CompanyCategoryRequest {
+    const TAG: &'static str = <CompanyCategory as Tdx>::TAG;
+    type Response = Vec<CompanyCategoryData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; CompanyCategory::LEN];
+        arr.copy_from_slice(CompanyCategory::SEND);
+        arr[12..14].copy_from_slice(&self.market.code().to_le_bytes());
+        arr[14..20].copy_from_slice(self.code.as_bytes());
+        arr.into()
+    }
+
+    /// 前 2 字节表示目录项数量，剩余字节中，每 152 字节使用 [`CompanyCategoryData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 2)?;
+        let count = crate::bytes_helper::u16_from_le_bytes(v, 0) as usize;
+        ensure_len(Self::TAG, v, 2 + count * 152)?;
+        Ok(v[2..]
+            .chunks_exact(152)
+            .take(count)
+            .map(CompanyCategoryData::parse)
+            .collect())
+    }
+}
+
+/// [`CompanyCategory`] 的解析结果：F10 中的一个栏目。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct CompanyCategoryData {
+    /// 栏目名称，比如 “公司概况”
+    pub name: String,
+    /// 栏目所在的文件名，比如 `000001.txt`
+    pub filename: String,
+    /// 栏目在文件中的起始位置
+    pub start: u32,
+    /// 栏目的字节长度
+    pub length: u32,
+}
+
+impl CompanyCategoryData {
+    /// 解析 [`CompanyCategory`] 的响应字节。传入长度为 152 字节序列。
+    /// ```python
+    /// (name, filename, start, length) = struct.unpack("<64s80sII", bytes) # python 表示方式
+    /// ```
+    /// 其中 name 为 GBK 编码，两个字符串都以 `\0` 结尾。
+    pub fn parse(bytes: &[u8]) -> Self {
+        use crate::{bytes_helper::u32_from_le_bytes, tcp::helper::gbk};
+        Self {
+            name: gbk(&bytes[..64]),
+            filename: gbk(&bytes[64..144]),
+            start: u32_from_le_bytes(bytes, 144),
+            length: u32_from_le_bytes(bytes, 148),
+        }
+    }
+}
+
+/// 查询公司信息（F10）某个栏目的内容。对应于 pytdx 中的 hq.get_company_info_content、
+/// GetCompanyInfoContent。
+///
+/// filename、start 和 length 来自于 [`CompanyCategory`] 的结果。
+#[derive(Debug, Clone)]
+pub struct CompanyContent<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    pub filename: &'d str,
+    pub start: u32,
+    pub length: u32,
+    pub response: Vec<u8>,
+    pub data: String,
+}
+
+/// 默认查询 #sz000001# 的 `000001.txt` 文件从 0 开始的 100 字节。
+impl<'d> Default for CompanyContent<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            filename: "000001.txt",
+            start: 0,
+            length: 100,
+            response: Vec::new(),
+            data: String::new(),
+        }
+    }
+}
+
+impl<'d> CompanyContent<'d> {
+    /// ## panic
+    /// 当 code 的字节长度不是 6 或者 filename 的字节长度超过 80 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str, filename: &'d str, start: u32, length: u32) -> Self {
+        let mut content = Self::default();
+        content
+            .market(market)
+            .code(code)
+            .filename(filename)
+            .start(start)
+            .length(length);
+        content
+    }
+
+    /// 从目录项构造请求。
+    pub fn from_category(market: Market, code: &'d str, category: &'d CompanyCategoryData) -> Self {
+        Self::new(
+            market,
+            code,
+            &category.filename,
+            category.start,
+            category.length,
+        )
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[12..14].copy_from_slice(&market.code().to_le_bytes());
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[14..20].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 修改文件名。
+    ///
+    /// ## panic
+    /// 当 filename 的字节长度超过 80 时，程序会 panic。
+    pub fn filename(&mut self, filename: &'d str) -> &mut Self {
+        self.filename = filename;
+        let name = &mut self.send[22..102];
+        name.fill(0);
+        name[..filename.len()].copy_from_slice(filename.as_bytes());
+        self
+    }
+
+    /// 修改起始位置。
+    pub fn start(&mut self, start: u32) -> &mut Self {
+        self.start = start;
+        self.send[102..106].copy_from_slice(&start.to_le_bytes());
+        self
+    }
+
+    /// 修改读取的字节长度。
+    pub fn length(&mut self, length: u32) -> &mut Self {
+        self.length = length;
+        self.send[106..110].copy_from_slice(&length.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> CompanyContentRequest {
+        CompanyContentRequest::new(
+            self.market,
+            self.code,
+            self.filename,
+            self.start,
+            self.length,
+        )
+    }
+}
+
+impl<'a> Tdx for CompanyContent<'a> {
+    type Item = str;
+
+    /// #sz000001# 的 `000001.txt` 文件从 0 开始的 100 字节的请求字节。长度为 114。
+    /// ```python
+    /// struct.pack("<H6sH80sIII", market, code, 0, filename, start, length, 0) # 后 104 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x07, 0x10, 0x9c, 0x00, 0x01, 0x68, 0x00, 0x68, 0x00, 0xd0, 0x02, 0x00, 0x00, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31, 0x2e, 0x74,
+        0x78, 0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x64, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    const TAG: &'static str = "公司信息内容";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`CompanyContent`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompanyContentRequest {
+    pub market: Market,
+    pub code: String,
+    pub filename: String,
+    pub start: u32,
+    pub length: u32,
+}
+
+impl CompanyContentRequest {
+    pub fn new(
+        market: Market,
+        code: impl Into<String>,
+        filename: impl Into<String>,
+        start: u32,
+        length: u32,
+    ) -> Self {
+        Self {
+            market,
+            code: code.into(),
+            filename: filename.into(),
+            start,
+            length,
+        }
+    }
+}
+
+impl Request for CompanyContentRequest {
+    const TAG: &'static str = <CompanyContent as Tdx>::TAG;
+    type Response = String;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 或者 filename 的字节长度超过 80 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; CompanyContent::LEN];
+        arr.copy_from_slice(CompanyContent::SEND);
+        arr[12..14].copy_from_slice(&self.market.code().to_le_bytes());
+        arr[14..20].copy_from_slice(self.code.as_bytes());
+        arr[22..102].fill(0);
+        arr[22..22 + self.filename.len()].copy_from_slice(self.filename.as_bytes());
+        arr[102..106].copy_from_slice(&self.start.to_le_bytes());
+        arr[106..110].copy_from_slice(&self.length.to_le_bytes());
+        arr.into()
+    }
+
+    /// 跳过前 10 字节，之后 2 字节表示内容的字节长度，剩余字节为 GBK 编码的文本。
+    /// 无法解码的字节会被替换成 `U+FFFD`。
+    ///
+    /// 剩余字节少于内容的字节长度时返回 [`Error::Parse`][crate::tcp::Error::Parse] 。
+    fn decode(&self, v: &[u8]) -> Result<String> {
+        ensure_len(Self::TAG, v, 12)?;
+        let len = crate::bytes_helper::u16_from_le_bytes(v, 10) as usize;
+        ensure_len(Self::TAG, v, 12 + len)?;
+        let (content, _, _) = encoding_rs::GBK.decode(&v[12..12 + len]);
+        Ok(content.into())
+    }
+}
+
+#[test]
+fn new_modify() {
+    let category = CompanyCategory::new(Market::SZ, "000001");
+    let mut category2 = CompanyCategory::new(Market::SH, "600000");
+    crate::tcp::tests::same_request(&category2, &category2.request());
+    category2.market(Market::SZ).code("000001");
+    compare!(CompanyCategory::default(), category, category2);
+
+    let content = CompanyContent::new(Market::SZ, "000001", "000001.txt", 0, 100);
+    let mut content2 = CompanyContent::new(Market::SH, "600000", "600000.txt.bak", 10, 10);
+    crate::tcp::tests::same_request(&content2, &content2.request());
+    content2
+        .market(Market::SZ)
+        .code("000001")
+        .filename("000001.txt")
+        .start(0)
+        .length(100);
+    compare!(CompanyContent::default(), content, content2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(CompanyCategory::default())?;
+    crate::tcp::tests::connection(CompanyContent::default())
+}
+
+#[test]
+fn parse() {
+    let entry = |name: &str, filename: &str, start: u32, length: u32| {
+        let mut bytes = [0; 152];
+        let (name, _, _) = encoding_rs::GBK.encode(name);
+        bytes[..name.len()].copy_from_slice(&name);
+        bytes[64..64 + filename.len()].copy_from_slice(filename.as_bytes());
+        bytes[144..148].copy_from_slice(&start.to_le_bytes());
+        bytes[148..].copy_from_slice(&length.to_le_bytes());
+        bytes
+    };
+    let mut arr = vec![0x02, 0x00];
+    arr.extend(entry("最新提示", "000001.txt", 0, 8612));
+    arr.extend(entry("公司概况", "000001.txt", 8612, 10473));
+
+    let mut category = CompanyCategory::default();
+    category.parse(arr).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(category.data, [
+        CompanyCategoryData { name: "最新提示".into(), filename: "000001.txt".into(), start: 0, length: 8612 },
+        CompanyCategoryData { name: "公司概况".into(), filename: "000001.txt".into(), start: 8612, length: 10473 },
+    ]);
+
+    let mut content = CompanyContent::from_category(Market::SZ, "000001", &category.data[1]);
+    assert_eq!(
+        &content.send[102..110],
+        &[0xa4, 0x21, 0, 0, 0xe9, 0x28, 0, 0]
+    );
+    let arr = vec![
+        0x00, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x00, 0x08, 0x00, 0xc6, 0xbd, 0xb0,
+        0xb2, 0xd2, 0xf8, 0xd0, 0xd0,
+    ];
+    content.parse(arr.clone()).unwrap();
+    assert_eq!(content.result(), "平安银行");
+    // 内容的字节长度超过剩余字节
+    let err = content.request().decode(&arr[..arr.len() - 1]).unwrap_err();
+    assert!(matches!(err, crate::tcp::Error::Parse { .. }), "{err}");
+}
@@ -0,0 +1,323 @@
+use crate::tcp::{ensure_len, Error, Request, Result, Tdx, Transport};
+use std::io::Write;
+
+/// 分块下载服务器上的文件（比如板块文件 `block_zs.dat`）。
+/// 对应于 pytdx 中的 hq.get_block_info、GetBlockInfo 以及 GetReportFile。
+///
+/// ## 注意
+/// 1. 只修改字段并不会更改待发送字节的内容。
+///    如果你需要修改查询条件，请使用相应的方法。
+/// 2. 每次最多返回 [`FileChunk::MAX_SIZE`] 字节，返回的字节为空时表示已经到达文件末尾。
+#[derive(Debug, Clone)]
+pub struct FileChunk<'d> {
+    pub send: Box<[u8]>,
+    /// 服务器上的文件名
+    pub filename: &'d str,
+    /// 从文件的第 offset 字节开始下载
+    pub offset: u32,
+    /// 下载的字节数，不超过 [`FileChunk::MAX_SIZE`]
+    pub size: u32,
+    pub response: Vec<u8>,
+}
+
+/// 默认下载 `block_zs.dat` 的第一块。
+impl<'d> Default for FileChunk<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            filename: "block_zs.dat",
+            offset: 0,
+            size: Self::MAX_SIZE,
+            response: Vec::new(),
+        }
+    }
+}
+
+impl<'d> FileChunk<'d> {
+    /// 服务器每次最多返回的字节数。
+    pub const MAX_SIZE: u32 = 0x7530;
+
+    /// ## panic
+    /// 当 filename 的字节长度超过 100 时，程序会 panic。
+    pub fn new(filename: &'d str, offset: u32, size: u32) -> Self {
+        let mut chunk = Self::default();
+        chunk.filename(filename).offset(offset).size(size);
+        chunk
+    }
+
+    /// 修改文件名。
+    ///
+    /// ## panic
+    /// 当 filename 的字节长度超过 100 时，程序会 panic。
+    pub fn filename(&mut self, filename: &'d str) -> &mut Self {
+        self.filename = filename;
+        let arr = &mut self.send[20..120];
+        arr.fill(0);
+        arr[..filename.len()].copy_from_slice(filename.as_bytes());
+        self
+    }
+
+    /// 修改起始位置。
+    pub fn offset(&mut self, offset: u32) -> &mut Self {
+        self.offset = offset;
+        self.send[12..16].copy_from_slice(&offset.to_le_bytes());
+        self
+    }
+
+    /// 修改下载的字节数。
+    pub fn size(&mut self, size: u32) -> &mut Self {
+        self.size = size;
+        self.send[16..20].copy_from_slice(&size.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> FileChunkRequest {
+        FileChunkRequest::new(self.filename, self.offset, self.size)
+    }
+}
+
+impl<'a> Tdx for FileChunk<'a> {
+    type Item = [u8];
+
+    /// 下载 `block_zs.dat` 第一块的请求字节。长度为 120。
+    /// ```python
+    /// struct.pack("<II100s", offset, size, filename) # 后 108 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x37, 0x18, 0x6a, 0x00, 0x01, 0x6e, 0x00, 0x6e, 0x00, 0xb9, 0x06, 0x00, 0x00, 0x00,
+        0x00, 0x30, 0x75, 0x00, 0x00, 0x62, 0x6c, 0x6f, 0x63, 0x6b, 0x5f, 0x7a, 0x73, 0x2e, 0x64,
+        0x61, 0x74, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    const TAG: &'static str = "文件下载";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    /// 前 4 字节为这一块的实际长度，剩余字节为文件内容。空的响应表示已经到达文件末尾。
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        if !v.is_empty() {
+            ensure_len(Self::TAG, &v, 4)?;
+        }
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        self.response.get(4..).unwrap_or_default()
+    }
+}
+
+/// [`FileChunk`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChunkRequest {
+    /// 服务器上的文件名
+    pub filename: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
+impl FileChunkRequest {
+    pub fn new(filename: impl Into<String>, offset: u32, size: u32) -> Self {
+        Self {
+            filename: filename.into(),
+            offset,
+            size,
+        }
+    }
+}
+
+impl Request for FileChunkRequest {
+    const TAG: &'static str = <FileChunk as Tdx>::TAG;
+    /// 文件内容
+    type Response = Vec<u8>;
+
+    /// ## panic
+    /// 当 filename 的字节长度超过 100 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; FileChunk::LEN];
+        arr[..12].copy_from_slice(&FileChunk::SEND[..12]);
+        arr[12..16].copy_from_slice(&self.offset.to_le_bytes());
+        arr[16..20].copy_from_slice(&self.size.to_le_bytes());
+        arr[20..20 + self.filename.len()].copy_from_slice(self.filename.as_bytes());
+        arr.into()
+    }
+
+    /// 去掉前 4 字节的实际长度，得到文件内容。
+    fn decode(&self, v: &[u8]) -> Result<Vec<u8>> {
+        if v.is_empty() {
+            return Ok(Vec::new());
+        }
+        ensure_len(Self::TAG, v, 4)?;
+        Ok(v[4..].to_vec())
+    }
+}
+
+/// 下载服务器上的整个文件（比如 `tdxhy.cfg`、`block_gn.dat`、`tdxfin/gpcw.txt`）到
+/// [`Write`] 中。对应于 pytdx 中的 hq.get_report_file_by_size 。
+///
+/// 内部使用 [`FileChunk`] 从头到尾逐块下载：
+/// 1. 已知文件大小（[`ReportFile::filesize`] 不为 0）时，下载到该大小为止，
+///    期间最多容忍 2 次空块，之后仍未下载完时返回 [`Error::ShortFrame`] ；
+/// 2. 未知文件大小时，遇到第一个空块即停止。
+#[derive(Debug, Clone)]
+pub struct ReportFile<'d> {
+    pub chunk: FileChunk<'d>,
+    /// 文件大小。为 0 表示未知。
+    pub filesize: u32,
+}
+
+impl<'d> ReportFile<'d> {
+    /// 已知文件大小时，允许收到空块的次数。
+    const MAX_EMPTY: u8 = 2;
+
+    /// ## panic
+    /// 当 filename 的字节长度超过 100 时，程序会 panic。
+    pub fn new(filename: &'d str) -> Self {
+        Self {
+            chunk: FileChunk::new(filename, 0, FileChunk::MAX_SIZE),
+            filesize: 0,
+        }
+    }
+
+    /// 设置文件大小，比如来自于 [`BlockMeta`][super::BlockMeta] 的查询结果。
+    pub fn filesize(&mut self, filesize: u32) -> &mut Self {
+        self.filesize = filesize;
+        self
+    }
+
+    /// 下载文件，并写入 sink 。返回下载的字节数。
+    pub fn download<W: Write>(
+        &mut self,
+        tcp: &mut (impl Transport + ?Sized),
+        sink: &mut W,
+    ) -> Result<u32> {
+        self.download_with_progress(tcp, sink, |_, _| ())
+    }
+
+    /// 下载文件，并写入 sink 。返回下载的字节数。
+    ///
+    /// 每下载一块，调用一次 `progress(已下载的字节数, 文件大小)`，文件大小未知时为 0 。
+    ///
+    /// 无论成功与否，调用结束之后 `chunk` 的 offset 都会恢复成 0 。
+    pub fn download_with_progress<W, F>(
+        &mut self,
+        tcp: &mut (impl Transport + ?Sized),
+        sink: &mut W,
+        progress: F,
+    ) -> Result<u32>
+    where
+        W: Write,
+        F: FnMut(u32, u32),
+    {
+        let res = self.download_chunks(tcp, sink, progress);
+        self.chunk.offset(0);
+        res
+    }
+
+    fn download_chunks<W, F>(
+        &mut self,
+        tcp: &mut (impl Transport + ?Sized),
+        sink: &mut W,
+        mut progress: F,
+    ) -> Result<u32>
+    where
+        W: Write,
+        F: FnMut(u32, u32),
+    {
+        let (filesize, mut downloaded, mut empty) = (self.filesize, 0u32, 0u8);
+        loop {
+            let data = self.chunk.offset(downloaded).recv_parsed(tcp)?;
+            if data.is_empty() {
+                empty += 1;
+                if filesize == 0 {
+                    return Ok(downloaded);
+                }
+                if empty > Self::MAX_EMPTY {
+                    return Err(Error::ShortFrame {
+                        expected: filesize as usize,
+                        found: downloaded as usize,
+                    });
+                }
+            } else {
+                sink.write_all(data)?;
+                downloaded += data.len() as u32;
+                progress(downloaded, filesize);
+            }
+            if filesize != 0 && downloaded >= filesize {
+                return Ok(downloaded);
+            }
+        }
+    }
+}
+
+#[test]
+fn new_modify() {
+    let chunk = FileChunk::new("block_zs.dat", 0, FileChunk::MAX_SIZE);
+    let mut chunk2 = FileChunk::new("tdxzsbase.cfg", 30000, 100);
+    crate::tcp::tests::same_request(&chunk2, &chunk2.request());
+    chunk2
+        .filename("block_zs.dat")
+        .offset(0)
+        .size(FileChunk::MAX_SIZE);
+    compare!(FileChunk::default(), chunk, chunk2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(FileChunk::default())?;
+
+    let mut tcp = crate::tcp::Tcp::new()?;
+    let (mut file, mut calls) = (Vec::new(), 0);
+    let n = ReportFile::new("tdxhy.cfg").download_with_progress(&mut tcp, &mut file, |n, _| {
+        calls += 1;
+        assert!(n as usize >= calls);
+    })?;
+    assert!(calls > 0);
+    assert_eq!(n as usize, file.len());
+    Ok(())
+}
+
+#[test]
+fn truncated() -> Result<()> {
+    let server = crate::tcp::mock::MockServer::start()?;
+    let mut tcp = server.connect()?;
+    // 模拟服务器对文件请求只返回空块
+    let mut file = ReportFile::new("tdxhy.cfg");
+    file.filesize(10);
+    let err = file.download(&mut tcp, &mut Vec::new()).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ShortFrame {
+            expected: 10,
+            found: 0
+        }
+    ));
+    assert_eq!(file.chunk.offset, 0);
+    assert_eq!(
+        ReportFile::new("tdxhy.cfg").download(&mut tcp, &mut Vec::new())?,
+        0
+    );
+    Ok(())
+}
+
+#[test]
+fn parse() {
+    let mut chunk = FileChunk::default();
+    chunk
+        .parse(vec![0x03, 0x00, 0x00, 0x00, 0x61, 0x62, 0x63])
+        .unwrap();
+    assert_eq!(chunk.result(), b"abc");
+    chunk.parse(vec![0x00, 0x00, 0x00, 0x00]).unwrap();
+    assert!(chunk.result().is_empty());
+}
@@ -0,0 +1,318 @@
+use crate::tcp::{ensure_len, Market, Request, Result, Tdx};
+
+/// 查询单只股票的财务信息快照（股本、资产、利润等）。
+/// 对应于 pytdx 中的 hq.get_finance_info、GetFinanceInfo。
+#[derive(Debug, Clone)]
+pub struct FinanceInfo<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    pub response: Vec<u8>,
+    pub data: FinanceInfoData,
+}
+
+/// 默认查询 #sz000001# 。
+impl<'d> Default for FinanceInfo<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            response: Vec::new(),
+            data: FinanceInfoData::default(),
+        }
+    }
+}
+
+impl<'d> FinanceInfo<'d> {
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str) -> Self {
+        let mut finance = Self::default();
+        finance.market(market).code(code);
+        finance
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[14] = market.code() as u8;
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[15..21].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> FinanceInfoRequest {
+        FinanceInfoRequest::new(self.market, self.code)
+    }
+}
+
+impl<'a> Tdx for FinanceInfo<'a> {
+    type Item = FinanceInfoData;
+
+    /// #sz000001# 财务信息的请求字节。长度为 21。
+    /// ```python
+    /// struct.pack("<HB6s", 1, market, code) # 后 9 字节，1 表示查询 1 只股票
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x1f, 0x18, 0x76, 0x00, 0x01, 0x0b, 0x00, 0x0b, 0x00, 0x10, 0x00, 0x01, 0x00, 0x00,
+        0x30, 0x30, 0x30, 0x30, 0x30, 0x31,
+    ];
+    const TAG: &'static str = "财务信息";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`FinanceInfo`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinanceInfoRequest {
+    pub market: Market,
+    pub code: String,
+}
+
+impl FinanceInfoRequest {
+    pub fn new(market: Market, code: impl Into<String>) -> Self {
+        Self {
+            market,
+            code: code.into(),
+        }
+    }
+}
+
+impl Request for FinanceInfoRequest {
+    const TAG: &'static str = <FinanceInfo as Tdx>::TAG;
+    type Response = FinanceInfoData;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; FinanceInfo::LEN];
+        arr.copy_from_slice(FinanceInfo::SEND);
+        arr[14] = self.market.code() as u8;
+        arr[15..21].copy_from_slice(self.code.as_bytes());
+        arr.into()
+    }
+
+    /// 跳过前 2 字节（股票数量，总是 1），剩余字节使用 [`FinanceInfoData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<FinanceInfoData> {
+        ensure_len(Self::TAG, v, 2 + FinanceInfoData::LEN)?;
+        Ok(FinanceInfoData::parse(&v[2..]))
+    }
+}
+
+/// [`FinanceInfo`] 的解析结果。
+///
+/// 除了特别说明的字段，股本的单位为股，金额的单位为元（响应中的原始单位为万，已乘以 10000）。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct FinanceInfoData {
+    pub market: u8,
+    /// 6 位股票代码
+    pub code: String,
+    /// 流通股本
+    pub liutongguben: f64,
+    /// 所属省份代码
+    pub province: u16,
+    /// 所属行业代码
+    pub industry: u16,
+    /// 更新日期，`YYYYMMDD` 格式
+    pub updated_date: u32,
+    /// 上市日期，`YYYYMMDD` 格式
+    pub ipo_date: u32,
+    /// 总股本
+    pub zongguben: f64,
+    /// 国家股
+    pub guojiagu: f64,
+    /// 发起人法人股
+    pub faqirenfarengu: f64,
+    /// 法人股
+    pub farengu: f64,
+    /// B 股
+    pub bgu: f64,
+    /// H 股
+    pub hgu: f64,
+    /// 职工股
+    pub zhigonggu: f64,
+    /// 总资产
+    pub zongzichan: f64,
+    /// 流动资产
+    pub liudongzichan: f64,
+    /// 固定资产
+    pub gudingzichan: f64,
+    /// 无形资产
+    pub wuxingzichan: f64,
+    /// 股东人数（原始值，未换算）
+    pub gudongrenshu: f64,
+    /// 流动负债
+    pub liudongfuzhai: f64,
+    /// 长期负债
+    pub changqifuzhai: f64,
+    /// 资本公积金
+    pub zibengongjijin: f64,
+    /// 净资产
+    pub jingzichan: f64,
+    /// 主营收入
+    pub zhuyingshouru: f64,
+    /// 主营利润
+    pub zhuyinglirun: f64,
+    /// 应收账款
+    pub yingshouzhangkuan: f64,
+    /// 营业利润
+    pub yingyelirun: f64,
+    /// 投资收益
+    pub touzishouyu: f64,
+    /// 经营现金流
+    pub jingyingxianjinliu: f64,
+    /// 总现金流
+    pub zongxianjinliu: f64,
+    /// 存货
+    pub cunhuo: f64,
+    /// 利润总额
+    pub lirunzonghe: f64,
+    /// 税后利润
+    pub shuihoulirun: f64,
+    /// 净利润
+    pub jinglirun: f64,
+    /// 未分配利润
+    pub weifenlirun: f64,
+    /// 每股净资产，单位：元（原始值，未换算）
+    pub meigujingzichan: f64,
+    /// 保留字段（原始值，未换算）
+    pub baoliu2: f64,
+}
+
+impl FinanceInfoData {
+    /// 响应中一只股票的财务信息的字节长度。
+    pub const LEN: usize = 143;
+
+    /// 每股收益 = 净利润 / 总股本。总股本为 0 时返回 0 。
+    pub fn eps(&self) -> f64 {
+        if self.zongguben == 0. {
+            0.
+        } else {
+            self.jinglirun / self.zongguben
+        }
+    }
+
+    /// 解析 [`FinanceInfo`] 的响应字节。传入长度至少为 143 字节的序列。
+    /// ```python
+    /// (market, code) = struct.unpack("<B6s", bytes[:7])
+    /// (liutongguben, province, industry, updated_date, ipo_date, zongguben, ...,
+    ///  meigujingzichan, baoliu2) = struct.unpack("<fHHII30f", bytes[7:]) # python 表示方式
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Self {
+        use crate::bytes_helper::{f32_from_le_bytes, u16_from_le_bytes, u32_from_le_bytes};
+
+        // 第 n 个（从 0 开始）30f 中的浮点数，保持原始值
+        let raw = |n: usize| f32_from_le_bytes(bytes, 23 + n * 4) as f64;
+        // 单位为万的值
+        let wan = |n: usize| raw(n) * 10000.;
+        Self {
+            market: bytes[0],
+            code: String::from_utf8_lossy(&bytes[1..7]).into(),
+            liutongguben: f32_from_le_bytes(bytes, 7) as f64 * 10000.,
+            province: u16_from_le_bytes(bytes, 11),
+            industry: u16_from_le_bytes(bytes, 13),
+            updated_date: u32_from_le_bytes(bytes, 15),
+            ipo_date: u32_from_le_bytes(bytes, 19),
+            zongguben: wan(0),
+            guojiagu: wan(1),
+            faqirenfarengu: wan(2),
+            farengu: wan(3),
+            bgu: wan(4),
+            hgu: wan(5),
+            zhigonggu: wan(6),
+            zongzichan: wan(7),
+            liudongzichan: wan(8),
+            gudingzichan: wan(9),
+            wuxingzichan: wan(10),
+            gudongrenshu: raw(11),
+            liudongfuzhai: wan(12),
+            changqifuzhai: wan(13),
+            zibengongjijin: wan(14),
+            jingzichan: wan(15),
+            zhuyingshouru: wan(16),
+            zhuyinglirun: wan(17),
+            yingshouzhangkuan: wan(18),
+            yingyelirun: wan(19),
+            touzishouyu: wan(20),
+            jingyingxianjinliu: wan(21),
+            zongxianjinliu: wan(22),
+            cunhuo: wan(23),
+            lirunzonghe: wan(24),
+            shuihoulirun: wan(25),
+            jinglirun: wan(26),
+            weifenlirun: wan(27),
+            meigujingzichan: raw(28),
+            baoliu2: raw(29),
+        }
+    }
+}
+
+#[test]
+fn new_modify() {
+    let finance = FinanceInfo::new(Market::SZ, "000001");
+    let mut finance2 = FinanceInfo::new(Market::SH, "600000");
+    crate::tcp::tests::same_request(&finance2, &finance2.request());
+    finance2.market(Market::SZ).code("000001");
+    compare!(FinanceInfo::default(), finance, finance2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(FinanceInfo::default())
+}
+
+#[test]
+fn parse() {
+    let mut arr = vec![0x01, 0x00, 0x00];
+    arr.extend(b"000001");
+    arr.extend(1940575.2f32.to_le_bytes());
+    arr.extend(18u16.to_le_bytes());
+    arr.extend(1u16.to_le_bytes());
+    arr.extend(20210820u32.to_le_bytes());
+    arr.extend(19910403u32.to_le_bytes());
+    let mut floats = [0f32; 30];
+    floats[0] = 1940591.8; // zongguben
+    floats[11] = 505893.; // gudongrenshu
+    floats[26] = 1758300.; // jinglirun
+    floats[28] = 19.27; // meigujingzichan
+    floats.iter().for_each(|f| arr.extend(f.to_le_bytes()));
+    assert_eq!(arr.len(), 2 + FinanceInfoData::LEN);
+
+    let mut finance = FinanceInfo::default();
+    finance.parse(arr).unwrap();
+    let data = finance.result();
+    assert_eq!(data.code, "000001");
+    assert_eq!(data.liutongguben, 1940575.25 * 10000.);
+    assert_eq!((data.province, data.industry), (18, 1));
+    assert_eq!((data.updated_date, data.ipo_date), (20210820, 19910403));
+    assert_eq!(data.zongguben, 1940591.75 * 10000.);
+    assert_eq!(data.gudongrenshu, 505893.);
+    assert_eq!(data.meigujingzichan, 19.27f32 as f64);
+    assert!((data.eps() - 0.906).abs() < 1e-3);
+}
@@ -1,4 +1,9 @@
-use crate::tcp::{helper::DateTime, Tdx};
+use crate::{
+    bytes_helper::u16_from_le_bytes,
+    tcp::{
+        ensure_len, helper::DateTime, Error, KlineCategory, Market, Request, Result, Tdx, Transport,
+    },
+};
 
 // ['获取股票行情', '参数：市场代码， 股票代码， 如： 0,000001 或 1,  600300',
 // get_security_quotes, '0,000001']),          (2, ['获取k线', '''category-> K线种类  0
@@ -21,24 +26,25 @@ use crate::tcp::{helper::DateTime, Tdx};
 #[derive(Debug, Clone)]
 pub struct Kline<'d> {
     pub send: Box<[u8]>,
-    pub market: u16,
+    pub market: Market,
     pub code: &'d str,
-    pub category: u16,
+    pub category: KlineCategory,
     pub start: u16,
     pub count: u16,
     pub response: Vec<u8>,
-    pub data: Vec<KlineData<'d>>,
+    pub data: Vec<KlineData>,
 }
 
 /// 为了对应 [`Kline::SEND`] 的含义，以下默认值值得注意：
-/// 1. category 默认为 9 （日线）；
+/// 1. category 默认为 [`KlineCategory::Day`] （日线）；
 /// 2. code 默认为 `000001`；
 /// 3. count 默认为 3；
 /// 4. KlineData.dt.hour 默认小时数为 15。
 #[rustfmt::skip]
 impl<'d> Default for Kline<'d> {
     fn default() -> Self {
-        Self { market: 0, code: "000001", category: 9, start: 0, count: 3,
+        Self { market: Market::SZ, code: "000001", category: KlineCategory::Day,
+               start: 0, count: 3,
                send:     { let mut v = [0; Self::LEN]; v.copy_from_slice(Self::SEND); v.into() },
                response: Vec::new(),
                data:     vec![KlineData::default(); 3], }
@@ -46,31 +52,26 @@ impl<'d> Default for Kline<'d> {
 }
 
 impl<'d> Kline<'d> {
-    /// 0 代表深市；1 代表沪市。
+    /// 单次请求最多返回的 K 线数量。
+    pub const MAX_COUNT: u16 = 800;
+
+    /// 带市场前缀的代码可以使用 [`Market::split_code`] 拆分。
     ///
     /// ## panic
     /// 当 code 的字节长度不是 6 时，程序会 panic。
     #[rustfmt::skip]
-    pub fn new(market: u16, code: &'d str, category: u16, start: u16, count: u16) -> Self {
+    pub fn new(market: Market, code: &'d str, category: KlineCategory,
+               start: u16, count: u16) -> Self {
         Self { market, code, category, start, count,
-               send: {
-                   let mut arr = [0; Self::LEN];
-                   arr.copy_from_slice(Self::SEND);
-                   arr[12..14].copy_from_slice(&market.to_le_bytes());
-                   arr[14..20].copy_from_slice(code.as_bytes());
-                   arr[20..22].copy_from_slice(&category.to_le_bytes());
-                   arr[24..26].copy_from_slice(&start.to_le_bytes());
-                   arr[26..28].copy_from_slice(&count.to_le_bytes());
-                   arr.into()
-               },
+               send: pack(market, code, category, start, count),
                response: Vec::new(),
                data: vec![KlineData::default(); count as usize] }
     }
 
     /// 修改市场。
-    pub fn market(&mut self, market: u16) -> &mut Self {
+    pub fn market(&mut self, market: Market) -> &mut Self {
         self.market = market;
-        self.send[12..14].copy_from_slice(&market.to_le_bytes());
+        self.send[12..14].copy_from_slice(&market.code().to_le_bytes());
         self
     }
 
@@ -85,9 +86,9 @@ impl<'d> Kline<'d> {
     }
 
     /// 修改 K 线类型。
-    pub fn category(&mut self, category: u16) -> &mut Self {
+    pub fn category(&mut self, category: KlineCategory) -> &mut Self {
         self.category = category;
-        self.send[20..22].copy_from_slice(&category.to_le_bytes());
+        self.send[20..22].copy_from_slice(&category.code().to_le_bytes());
         self
     }
 
@@ -112,10 +113,82 @@ impl<'d> Kline<'d> {
         self.send[26..28].copy_from_slice(&count.to_le_bytes());
         self
     }
+
+    /// 获取从 start 开始往前的所有 K 线，返回按时间顺序排列的结果。
+    ///
+    /// 由于服务器每次最多返回 [`Kline::MAX_COUNT`] 根 K 线，此方法每次向前请求一页，
+    /// 直到服务器不再返回数据为止。分页边界处重复的 K 线（比如在盘中请求时，
+    /// 新的 K 线使得起始位置发生偏移）会被去除。
+    ///
+    /// 调用结束之后（包括出错时），start 和 count 会恢复成调用之前的值，
+    /// data 字段为最后一页的数据。
+    ///
+    /// 请求的起始位置为 u16 ，所以最多只能获取 65535 根之前的 K 线；
+    /// 下一页的起始位置超出这个范围时，停止请求，返回已经得到的 K 线。
+    pub fn recv_all(&mut self, tcp: &mut (impl Transport + ?Sized)) -> Result<Vec<KlineData>> {
+        let (start, count) = (self.start, self.count);
+        self.count(Self::MAX_COUNT);
+        let pages = self.recv_pages(tcp, start);
+        self.start(start).count(count);
+        Ok(merge_pages(pages?))
+    }
+
+    /// 从 start 开始往前逐页请求，直到服务器不再返回数据、或者下一页的起始位置超出 u16 为止。
+    fn recv_pages(
+        &mut self,
+        tcp: &mut (impl Transport + ?Sized),
+        start: u16,
+    ) -> Result<Vec<Vec<KlineData>>> {
+        let mut pages = Vec::new();
+        let mut page_start = Some(start);
+        while let Some(start) = page_start {
+            let page = self.start(start).recv_parsed(tcp)?;
+            if page.is_empty() {
+                break;
+            }
+            page_start = start.checked_add(page.len() as u16);
+            pages.push(page.to_vec());
+        }
+        Ok(pages)
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> KlineRequest {
+        KlineRequest::new(
+            self.market,
+            self.code,
+            self.category,
+            self.start,
+            self.count,
+        )
+    }
+}
+
+/// [`Kline::SEND`] 的格式，[`Kline`]、[`KlineRequest`] 和 [`IndexKlineRequest`] 共用。
+fn pack(market: Market, code: &str, category: KlineCategory, start: u16, count: u16) -> Box<[u8]> {
+    let mut arr = [0; Kline::LEN];
+    arr.copy_from_slice(Kline::SEND);
+    arr[12..14].copy_from_slice(&market.code().to_le_bytes());
+    arr[14..20].copy_from_slice(code.as_bytes());
+    arr[20..22].copy_from_slice(&category.code().to_le_bytes());
+    arr[24..26].copy_from_slice(&start.to_le_bytes());
+    arr[26..28].copy_from_slice(&count.to_le_bytes());
+    arr.into()
+}
+
+/// 合并从新到旧排列的多页 K 线，按时间顺序返回，并去除时间重复的 K 线。
+fn merge_pages(pages: Vec<Vec<KlineData>>) -> Vec<KlineData> {
+    let mut all: Vec<KlineData> = Vec::with_capacity(pages.iter().map(Vec::len).sum());
+    for kline in pages.into_iter().rev().flatten() {
+        if all.last().is_none_or(|last| last.dt < kline.dt) {
+            all.push(kline);
+        }
+    }
+    all
 }
 
 impl<'a> Tdx for Kline<'a> {
-    type Item = [KlineData<'a>];
+    type Item = [KlineData];
 
     /// #sz000001# 最近三天日线的请求字节。长度为 38。
     /// ```python
@@ -128,36 +201,203 @@ impl<'a> Tdx for Kline<'a> {
     ];
     const TAG: &'static str = "日线";
 
-    fn send(&mut self) -> &[u8] {
+    fn send(&self) -> &[u8] {
         &self.send
     }
 
+    /// 当已经到达最早的 K 线时，响应的数量会少于查询数量，此时 data 字段的长度以响应为准。
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`Kline`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KlineRequest {
+    pub market: Market,
+    pub code: String,
+    pub category: KlineCategory,
+    pub start: u16,
+    pub count: u16,
+}
+
+impl KlineRequest {
+    /// 参数含义与 [`Kline::new`] 相同。
+    pub fn new(
+        market: Market,
+        code: impl Into<String>,
+        category: KlineCategory,
+        start: u16,
+        count: u16,
+    ) -> Self {
+        Self {
+            market,
+            code: code.into(),
+            category,
+            start,
+            count,
+        }
+    }
+}
+
+impl Request for KlineRequest {
+    const TAG: &'static str = <Kline as Tdx>::TAG;
+    type Response = Vec<KlineData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        pack(
+            self.market,
+            &self.code,
+            self.category,
+            self.start,
+            self.count,
+        )
+    }
+
+    /// 前 2 字节表示 K 线数量，剩余字节使用 [`KlineData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 2)?;
+        let (count, mut pos, mut base) = (u16_from_le_bytes(v, 0), 2, 0);
+        if count > self.count {
+            let reason = format!("请求 {} 根 K 线，得到 {count} 根", self.count);
+            return Err(Error::parse(Self::TAG, reason));
+        }
+        let mut data = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            ensure_len(Self::TAG, v, pos + KlineData::MIN_LEN)?;
+            data.push(KlineData::parse(
+                v,
+                &mut pos,
+                &mut base,
+                &self.code,
+                self.category,
+            )?);
+        }
+        ensure_len(Self::TAG, v, pos)?;
+        Ok(data)
+    }
+}
+
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct KlineData {
+    pub dt: DateTime,
+    pub code: Box<str>,
+    pub open: f64,
+    pub close: f64,
+    pub high: f64,
+    pub low: f64,
+    /// 成交量，单位：股
+    pub vol: f64,
+    /// 成交额，单位：元
+    pub amount: f64,
+}
+
+impl KlineData {
+    /// 一根 K 线至少占的字节数：4 字节时间、4 个至少 1 字节的价格、8 字节成交量和成交额。
+    pub const MIN_LEN: usize = 16;
+
+    /// 从 `pos` 位置开始解析一根 K 线，并把 `pos` 移动到下一根 K 线的起始位置。
+    ///
+    /// `base` 为上一根 K 线的收盘价（放大了 1000 倍），第一根 K 线传入 0 。
+    /// 解析之后，`base` 被更新为这根 K 线的收盘价。
+    ///
+    /// 字节不足一根 K 线、或者价格超出 i32 的范围时返回 [`Error::Parse`] 。
     #[rustfmt::skip]
-    fn parse(&mut self, v: Vec<u8>) {
+    pub fn parse(v: &[u8], pos: &mut usize, base: &mut i32, code: &str,
+                 category: KlineCategory) -> Result<Self> {
         use crate::{
-            tcp::helper::{datetime, price, vol_amount},
-            bytes_helper::{u16_from_le_bytes, u32_from_le_bytes}
+            tcp::helper::{add_price, datetime, price, vol_amount},
+            bytes_helper::u32_from_le_bytes,
         };
 
-        let (count, mut pos, mut base) = (u16_from_le_bytes(&v, 0), 2, 0);
-        debug_assert_eq!(count, self.count);
-        for item in self.data.iter_mut() {
-            let dt = datetime(&v[pos..pos + 4], self.category);
-            pos += 4;
-            let open = price(&v, &mut pos);
-            let close = price(&v, &mut pos);
-
-            *item = KlineData { dt, code: self.code,
-                                open:   { base += open; base as f64 / 1000. },
-                                close:  real_price(close, base),
-                                high:   real_price(price(&v, &mut pos), base),
-                                low:    real_price(price(&v, &mut pos), base),
-                                vol:    { pos += 4; vol_amount(u32_from_le_bytes(&v, pos - 4) as i32) },
-                                amount: { pos += 4; vol_amount(u32_from_le_bytes(&v, pos - 4) as i32) }};
-
-            base += close;
+        ensure_len("K 线", v, *pos + 4)?;
+        let dt = datetime(&v[*pos..*pos + 4], category);
+        *pos += 4;
+        // 开盘价相对于上一根 K 线的收盘价，其余价格相对于开盘价
+        let open = add_price(*base, price(v, pos)?)?;
+        let close = add_price(open, price(v, pos)?)?;
+        let high = add_price(open, price(v, pos)?)?;
+        let low = add_price(open, price(v, pos)?)?;
+        ensure_len("K 线", v, *pos + 8)?;
+
+        let real = |p: i32| p as f64 / 1000.;
+        let kline = Self { dt, code: code.into(),
+                           open: real(open), close: real(close), high: real(high), low: real(low),
+                           vol:    { *pos += 4; vol_amount(u32_from_le_bytes(v, *pos - 4) as i32) },
+                           amount: { *pos += 4; vol_amount(u32_from_le_bytes(v, *pos - 4) as i32) } };
+
+        *base = close;
+        Ok(kline)
+    }
+}
+
+/// 查询指数 K 线。对应于 pytdx 中的 hq.get_index_bars、GetIndexBarsCmd。
+///
+/// 请求字节与 [`Kline`] 完全一致，只是响应的每根 K 线多了上涨家数和下跌家数，
+/// 所以需要使用 [`IndexKline`] 来解析。修改查询条件请调用 `kline` 字段的方法，
+/// 比如 `index.kline.start(800)` 。
+#[derive(Debug, Clone)]
+pub struct IndexKline<'d> {
+    pub kline: Kline<'d>,
+    pub data: Vec<IndexKlineData>,
+}
+
+/// 默认查询 #sh000001# 最近三天日线。
+impl<'d> Default for IndexKline<'d> {
+    fn default() -> Self {
+        Self::new(Market::SH, "000001", KlineCategory::Day, 0, 3)
+    }
+}
+
+impl<'d> IndexKline<'d> {
+    /// 参数含义与 [`Kline::new`] 相同。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(
+        market: Market,
+        code: &'d str,
+        category: KlineCategory,
+        start: u16,
+        count: u16,
+    ) -> Self {
+        Self {
+            kline: Kline::new(market, code, category, start, count),
+            data: Vec::with_capacity(count as usize),
         }
-        self.response = v;
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> IndexKlineRequest {
+        IndexKlineRequest {
+            kline: self.kline.request(),
+        }
+    }
+}
+
+impl<'a> Tdx for IndexKline<'a> {
+    type Item = [IndexKlineData];
+
+    /// 与 [`Kline::SEND`] 相同。
+    const SEND: &'static [u8] = Kline::SEND;
+    const TAG: &'static str = "指数日线";
+
+    fn send(&self) -> &[u8] {
+        self.kline.send()
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.kline.response = v;
+        Ok(())
     }
 
     fn result(&self) -> &Self::Item {
@@ -165,10 +405,65 @@ impl<'a> Tdx for Kline<'a> {
     }
 }
 
+/// [`IndexKline`] 的请求参数，见 [`Request`] 。请求字节与 [`KlineRequest`] 完全一致。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexKlineRequest {
+    pub kline: KlineRequest,
+}
+
+impl IndexKlineRequest {
+    /// 参数含义与 [`Kline::new`] 相同。
+    pub fn new(
+        market: Market,
+        code: impl Into<String>,
+        category: KlineCategory,
+        start: u16,
+        count: u16,
+    ) -> Self {
+        Self {
+            kline: KlineRequest::new(market, code, category, start, count),
+        }
+    }
+}
+
+impl Request for IndexKlineRequest {
+    const TAG: &'static str = <IndexKline as Tdx>::TAG;
+    type Response = Vec<IndexKlineData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        self.kline.send()
+    }
+
+    /// 每根 K 线先按 [`KlineData::parse`] 解析，之后 4 字节为上涨家数和下跌家数。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 2)?;
+        let (count, mut pos, mut base) = (u16_from_le_bytes(v, 0), 2, 0);
+        if count > self.kline.count {
+            let reason = format!("请求 {} 根 K 线，得到 {count} 根", self.kline.count);
+            return Err(Error::parse(Self::TAG, reason));
+        }
+        let (code, category) = (&self.kline.code, self.kline.category);
+        let mut data = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            ensure_len(Self::TAG, v, pos + KlineData::MIN_LEN + 4)?;
+            let kline = KlineData::parse(v, &mut pos, &mut base, code, category)?;
+            ensure_len(Self::TAG, v, pos + 4)?;
+            let up_count = u16_from_le_bytes(v, pos);
+            let down_count = u16_from_le_bytes(v, pos + 2);
+            pos += 4;
+            data.push(IndexKlineData::new(kline, up_count, down_count));
+        }
+        Ok(data)
+    }
+}
+
+/// [`IndexKline`] 的解析结果：[`KlineData`] 的字段加上涨跌家数。
 #[derive(Debug, Default, Clone, serde::Serialize)]
-pub struct KlineData<'d> {
+pub struct IndexKlineData {
     pub dt: DateTime,
-    pub code: &'d str,
+    pub code: Box<str>,
     pub open: f64,
     pub close: f64,
     pub high: f64,
@@ -177,63 +472,57 @@ pub struct KlineData<'d> {
     pub vol: f64,
     /// 成交额，单位：元
     pub amount: f64,
+    /// 上涨家数
+    pub up_count: u16,
+    /// 下跌家数
+    pub down_count: u16,
 }
 
-// impl<'d> KlineData<'d> {
-//     pub fn parse(v: &'d [u8], mut pos: usize, mut base: i32, code: &'d str, category: u16)
-//                  -> (usize, i32, KlineData<'d>) {
-//         use crate::{
-//             bytes_helper::u32_from_le_bytes,
-//             tcp::helper::{datetime, price, vol_amount},
-//         };
-//         let dt = datetime(&v[pos..pos + 4], category);
-//         pos += 4;
-//         let open = price(&v, &mut pos);
-//         let close = price(&v, &mut pos);
-//
-//         #[rustfmt::skip]
-//         let kline = Self { dt, code,
-//                            open: { base += open; base as f64 / 1000.  },
-//                            close: real_price(close, base),
-//                            high: real_price(price(&v, &mut pos), base),
-//                            low: real_price(price(&v, &mut pos), base),
-//                            vol: { pos += 4; vol_amount(u32_from_le_bytes(&v, pos - 4) as
-// i32) },                            amount: { pos += 4; vol_amount(u32_from_le_bytes(&v, pos
-// - 4) as i32) } };
-//
-//         base += close;
-//         (pos, base, kline)
-//     }
-// }
-
-#[inline]
-fn real_price(p: i32, base: i32) -> f64 {
-    (p + base) as f64 / 1000.
+impl IndexKlineData {
+    #[rustfmt::skip]
+    pub fn new(kline: KlineData, up_count: u16, down_count: u16) -> Self {
+        let KlineData { dt, code, open, close, high, low, vol, amount } = kline;
+        Self { dt, code, open, close, high, low, vol, amount, up_count, down_count }
+    }
 }
 
 #[test]
 fn day_new_modify() {
-    let day = Kline::new(0, "000001", 9, 0, 3);
-    let mut day2 = Kline::new(1, "000000", 0, 1, 1);
-    day2.market(0).code("000001").category(9).start(0).count(3);
+    use KlineCategory::{Day, Min5};
+    let day = Kline::new(Market::SZ, "000001", Day, 0, 3);
+    let mut day2 = Kline::new(Market::SH, "000000", Min5, 1, 1);
+    crate::tcp::tests::same_request(&day2, &day2.request());
+    day2.market(Market::SZ)
+        .code("000001")
+        .category(Day)
+        .start(0)
+        .count(3);
     compare!(Kline::default(), day, day2);
+
+    let index = IndexKline::new(Market::SH, "000300", Min5, 1, 1);
+    crate::tcp::tests::same_request(&index, &index.request());
 }
 
 #[test]
-fn connection() -> std::io::Result<()> {
+fn connection() -> crate::tcp::Result<()> {
     crate::tcp::tests::connection(Kline::default())
 }
 
-#[test]
-fn parse() {
-    let mut day = Kline::default();
-    let arr = vec![
+#[cfg(test)]
+fn day_parse_bytes() -> Vec<u8> {
+    vec![
         0x03, 0x00, 0xeb, 0x64, 0x34, 0x01, 0xb4, 0x9a, 0x02, 0xe4, 0x06, 0x9c, 0x03, 0xc2, 0x07,
         0xe8, 0x6f, 0xa8, 0x49, 0x59, 0xf7, 0x12, 0x4f, 0xec, 0x64, 0x34, 0x01, 0xd0, 0x01, 0xfa,
         0x03, 0x90, 0x01, 0xc4, 0x04, 0x00, 0x81, 0x9a, 0x49, 0xb7, 0xb1, 0x03, 0x4f, 0xef, 0x64,
         0x34, 0x01, 0xcc, 0x02, 0xa8, 0x05, 0x96, 0x07, 0xd6, 0x02, 0xd8, 0x3d, 0x8b, 0x49, 0x4b,
         0xf0, 0xeb, 0x4e,
-    ];
+    ]
+}
+
+#[test]
+fn parse() {
+    let mut day = Kline::default();
+    let arr = day_parse_bytes();
     let res = [
         KlineData {
             dt: DateTime {
@@ -243,7 +532,7 @@ fn parse() {
                 hour: 15,
                 minute: 0,
             },
-            code: "000001",
+            code: "000001".into(),
             open: 18.1,
             close: 17.68,
             high: 18.32,
@@ -259,7 +548,7 @@ fn parse() {
                 hour: 15,
                 minute: 0,
             },
-            code: "000001",
+            code: "000001".into(),
             open: 17.6,
             close: 17.35,
             high: 17.68,
@@ -275,7 +564,7 @@ fn parse() {
                 hour: 15,
                 minute: 0,
             },
-            code: "000001",
+            code: "000001".into(),
             open: 17.21,
             close: 17.57,
             high: 17.68,
@@ -284,6 +573,128 @@ fn parse() {
             amount: 1979196800.0,
         },
     ];
-    day.parse(arr);
+    day.parse(arr).unwrap();
     compare!(res, day.data.as_slice());
 }
+
+#[test]
+fn index_connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(IndexKline::default())
+}
+
+#[test]
+fn index_parse() {
+    let mut index = IndexKline::default();
+    // 在 `parse` 测试的每根 K 线之后加上涨跌家数
+    let arr = vec![
+        0x03, 0x00, 0xeb, 0x64, 0x34, 0x01, 0xb4, 0x9a, 0x02, 0xe4, 0x06, 0x9c, 0x03, 0xc2, 0x07,
+        0xe8, 0x6f, 0xa8, 0x49, 0x59, 0xf7, 0x12, 0x4f, 0xd2, 0x04, 0x80, 0x0d, 0xec, 0x64, 0x34,
+        0x01, 0xd0, 0x01, 0xfa, 0x03, 0x90, 0x01, 0xc4, 0x04, 0x00, 0x81, 0x9a, 0x49, 0xb7, 0xb1,
+        0x03, 0x4f, 0x40, 0x0e, 0x6c, 0x04, 0xef, 0x64, 0x34, 0x01, 0xcc, 0x02, 0xa8, 0x05, 0x96,
+        0x07, 0xd6, 0x02, 0xd8, 0x3d, 0x8b, 0x49, 0x4b, 0xf0, 0xeb, 0x4e, 0x00, 0x00, 0x00, 0x00,
+    ];
+    // K 线数量多于请求的数量
+    let err = IndexKlineRequest::new(Market::SH, "000001", KlineCategory::Day, 0, 2)
+        .decode(&arr)
+        .unwrap_err();
+    assert!(matches!(err, Error::Parse { .. }), "{err}");
+    index.parse(arr).unwrap();
+    let breadth: Vec<_> = index
+        .data
+        .iter()
+        .map(|d| (d.up_count, d.down_count))
+        .collect();
+    assert_eq!(breadth, [(1234, 3456), (3648, 1132), (0, 0)]);
+    let mut day = Kline::default();
+    day.parse(day_parse_bytes()).unwrap();
+    let klines: Vec<_> = index
+        .data
+        .iter()
+        .map(|d| (d.dt.clone(), d.open, d.close, d.vol))
+        .collect();
+    let days: Vec<_> = day
+        .data
+        .iter()
+        .map(|d| (d.dt.clone(), d.open, d.close, d.vol))
+        .collect();
+    assert_eq!(klines, days);
+}
+
+#[test]
+fn parse_partial() {
+    let mut day = Kline::new(Market::SZ, "000001", KlineCategory::Day, 0, 5);
+    day.parse(day_parse_bytes()).unwrap();
+    assert_eq!(day.data.len(), 3);
+    assert_eq!(day.count, 5);
+
+    // 响应的字节不完整、K 线数量多于请求的数量
+    let mut truncated = day_parse_bytes();
+    truncated.truncate(truncated.len() - 8);
+    assert!(day.parse(truncated).is_err());
+    assert!(Kline::new(Market::SZ, "000001", KlineCategory::Day, 0, 2)
+        .parse(day_parse_bytes())
+        .is_err());
+}
+
+#[test]
+fn recv_all_error() -> Result<()> {
+    // 连接之后立即关闭，请求失败
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let server = std::thread::spawn(move || listener.accept().map(drop));
+    let mut tcp = crate::tcp::Tcp::builder().packs(false).connect(&addr)?;
+    server.join().unwrap()?;
+
+    let mut kline = Kline::new(Market::SZ, "000001", KlineCategory::Day, 5, 3);
+    assert!(kline.recv_all(&mut tcp).is_err());
+    assert_eq!((kline.start, kline.count), (5, 3));
+    assert_eq!(
+        kline.send,
+        Kline::new(Market::SZ, "000001", KlineCategory::Day, 5, 3).send
+    );
+    Ok(())
+}
+
+#[test]
+fn recv_all_start_overflow() -> Result<()> {
+    use crate::tcp::mock::{frame, respond, MockServer};
+
+    // 无论 start 是多少，都返回同样的三根 K 线
+    let server = MockServer::start_with(|request| match u16_from_le_bytes(request, 10) {
+        0x052d => frame(request, &day_parse_bytes()),
+        _ => respond(request),
+    })?;
+    let mut tcp = server.connect()?;
+    let mut kline = Kline::new(Market::SZ, "000001", KlineCategory::Day, u16::MAX - 5, 3);
+    // 第二页的起始位置为 u16::MAX - 2 ，第三页超出 u16 ，停止请求并返回已经得到的 K 线
+    let all = kline.recv_all(&mut tcp)?;
+    assert_eq!(all.len(), 3);
+    assert_eq!((kline.start, kline.count), (u16::MAX - 5, 3));
+    Ok(())
+}
+
+#[test]
+fn merge() {
+    let kline = |day: u16| KlineData {
+        dt: DateTime {
+            year: 2021,
+            month: 9,
+            day,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let days = |v: &[u16]| v.iter().copied().map(kline).collect::<Vec<_>>();
+    // 第二页与第一页有一根重复的 K 线
+    let all = merge_pages(vec![days(&[24, 27, 28]), days(&[22, 23, 24]), days(&[17])]);
+    let all: Vec<_> = all.iter().map(|k| k.dt.day).collect();
+    assert_eq!(all, [17, 22, 23, 24, 27, 28]);
+}
+
+#[test]
+fn all_connection() -> crate::tcp::Result<()> {
+    let all = Kline::default().recv_all(&mut crate::tcp::Tcp::new()?)?;
+    assert!(all.len() > Kline::MAX_COUNT as usize);
+    assert!(all.windows(2).all(|w| w[0].dt < w[1].dt));
+    Ok(())
+}
@@ -0,0 +1,378 @@
+use crate::tcp::{ensure_len, Market, Request, Result, Tdx};
+
+/// 查询当日分时数据（240 个点）。对应于 pytdx 中的 hq.get_minute_time_data、GetMinuteTimeData。
+/// ## 注意
+/// 只修改字段并不会更改待发送字节的内容。
+/// 如果你需要修改查询条件，请使用相应的方法。
+#[derive(Debug, Clone)]
+pub struct MinuteTime<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    pub response: Vec<u8>,
+    pub data: Vec<MinuteTimeData>,
+}
+
+/// 默认查询 #sz000001# 。
+impl<'d> Default for MinuteTime<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<'d> MinuteTime<'d> {
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str) -> Self {
+        let mut minute = Self::default();
+        minute.market(market).code(code);
+        minute
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[12..14].copy_from_slice(&market.code().to_le_bytes());
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[14..20].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> MinuteTimeRequest {
+        MinuteTimeRequest::new(self.market, self.code)
+    }
+}
+
+impl<'a> Tdx for MinuteTime<'a> {
+    type Item = [MinuteTimeData];
+
+    /// #sz000001# 当日分时的请求字节。长度为 24。
+    /// ```python
+    /// struct.pack("<H6sI", market, code, 0) # 后 12 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x1b, 0x08, 0x00, 0x01, 0x01, 0x0e, 0x00, 0x0e, 0x00, 0x1d, 0x05, 0x00, 0x00, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x00, 0x00, 0x00,
+    ];
+    const TAG: &'static str = "分时";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`MinuteTime`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinuteTimeRequest {
+    pub market: Market,
+    pub code: String,
+}
+
+impl MinuteTimeRequest {
+    pub fn new(market: Market, code: impl Into<String>) -> Self {
+        Self {
+            market,
+            code: code.into(),
+        }
+    }
+}
+
+impl Request for MinuteTimeRequest {
+    const TAG: &'static str = <MinuteTime as Tdx>::TAG;
+    type Response = Vec<MinuteTimeData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; MinuteTime::LEN];
+        arr.copy_from_slice(MinuteTime::SEND);
+        arr[12..14].copy_from_slice(&self.market.code().to_le_bytes());
+        arr[14..20].copy_from_slice(self.code.as_bytes());
+        arr.into()
+    }
+
+    /// 前 2 字节表示数量，跳过 2 个未知字节之后使用 [`MinuteTimeData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        MinuteTimeData::check(Self::TAG, v, 4)?;
+        MinuteTimeData::parse(v, 4)
+    }
+}
+
+/// 查询历史某日的分时数据（240 个点）。
+/// 对应于 pytdx 中的 hq.get_history_minute_time_data、GetHistoryMinuteTimeData。
+/// ## 注意
+/// 只修改字段并不会更改待发送字节的内容。
+/// 如果你需要修改查询条件，请使用相应的方法。
+#[derive(Debug, Clone)]
+pub struct HistoryMinuteTime<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    /// `YYYYMMDD` 格式的日期，比如 `20210923`。
+    pub date: u32,
+    pub response: Vec<u8>,
+    pub data: Vec<MinuteTimeData>,
+}
+
+/// 默认查询 #sz000001# 在 20210923 的分时数据。
+impl<'d> Default for HistoryMinuteTime<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            date: 20210923,
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<'d> HistoryMinuteTime<'d> {
+    /// date 为 `YYYYMMDD` 格式的日期。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str, date: u32) -> Self {
+        let mut minute = Self::default();
+        minute.market(market).code(code).date(date);
+        minute
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[16] = market.code() as u8;
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[17..23].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 修改日期。非交易日或者过于久远的日期会得到空的结果。
+    pub fn date(&mut self, date: u32) -> &mut Self {
+        self.date = date;
+        self.send[12..16].copy_from_slice(&date.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> HistoryMinuteTimeRequest {
+        HistoryMinuteTimeRequest::new(self.market, self.code, self.date)
+    }
+}
+
+impl<'a> Tdx for HistoryMinuteTime<'a> {
+    type Item = [MinuteTimeData];
+
+    /// #sz000001# 在 20210923 的分时请求字节。长度为 23。
+    /// ```python
+    /// struct.pack("<IB6s", date, market, code) # 后 11 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x01, 0x30, 0x00, 0x01, 0x01, 0x0d, 0x00, 0x0d, 0x00, 0xb4, 0x0f, 0xeb, 0x64, 0x34,
+        0x01, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31,
+    ];
+    const TAG: &'static str = "历史分时";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`HistoryMinuteTime`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryMinuteTimeRequest {
+    pub market: Market,
+    pub code: String,
+    pub date: u32,
+}
+
+impl HistoryMinuteTimeRequest {
+    /// date 为 `YYYYMMDD` 格式的日期。
+    pub fn new(market: Market, code: impl Into<String>, date: u32) -> Self {
+        Self {
+            market,
+            code: code.into(),
+            date,
+        }
+    }
+}
+
+impl Request for HistoryMinuteTimeRequest {
+    const TAG: &'static str = <HistoryMinuteTime as Tdx>::TAG;
+    type Response = Vec<MinuteTimeData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; HistoryMinuteTime::LEN];
+        arr.copy_from_slice(HistoryMinuteTime::SEND);
+        arr[12..16].copy_from_slice(&self.date.to_le_bytes());
+        arr[16] = self.market.code() as u8;
+        arr[17..23].copy_from_slice(self.code.as_bytes());
+        arr.into()
+    }
+
+    /// 前 2 字节表示数量，跳过 4 个未知字节之后使用 [`MinuteTimeData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        MinuteTimeData::check(Self::TAG, v, 6)?;
+        MinuteTimeData::parse(v, 6)
+    }
+}
+
+/// 分时数据中的一个点。价格单位为元，成交量单位为手。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct MinuteTimeData {
+    pub price: f64,
+    /// 原始值，含义未确认（疑似与均价有关），故不做换算。
+    pub reversed: i32,
+    pub vol: i32,
+}
+
+impl MinuteTimeData {
+    /// 检查有效数据的长度：前 2 字节表示数量，从 `pos` 开始每个点至少 3 字节。
+    pub fn check(tag: &'static str, v: &[u8], pos: usize) -> Result<()> {
+        ensure_len(tag, v, 2)?;
+        let count = crate::bytes_helper::u16_from_le_bytes(v, 0) as usize;
+        ensure_len(tag, v, pos + count * 3)
+    }
+
+    /// 解析分时数据。`v` 的前 2 字节表示数量，`pos` 为第一个点的起始位置。
+    ///
+    /// 每个点由三个 [`price`][crate::tcp::helper::price] 组成：价格（相对于上一个点的差值，
+    /// 放大了 100 倍）、未知值和成交量。
+    ///
+    /// 字节不足时返回 [`Error::Parse`][crate::tcp::Error::Parse] 。
+    pub fn parse(v: &[u8], mut pos: usize) -> Result<Vec<Self>> {
+        use crate::{
+            bytes_helper::u16_from_le_bytes,
+            tcp::helper::{add_price, price},
+        };
+
+        let count = u16_from_le_bytes(v, 0) as usize;
+        let mut base = 0;
+        (0..count)
+            .map(|_| {
+                base = add_price(base, price(v, &mut pos)?)?;
+                Ok(Self {
+                    price: base as f64 / 100.,
+                    reversed: price(v, &mut pos)?,
+                    vol: price(v, &mut pos)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn new_modify() {
+    let minute = MinuteTime::new(Market::SZ, "000001");
+    let mut minute2 = MinuteTime::new(Market::SH, "600000");
+    crate::tcp::tests::same_request(&minute2, &minute2.request());
+    minute2.market(Market::SZ).code("000001");
+    compare!(MinuteTime::default(), minute, minute2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(MinuteTime::default())
+}
+
+#[test]
+fn parse() {
+    let mut minute = MinuteTime::default();
+    let arr = vec![
+        0x03, 0x00, 0x00, 0x00, 0xab, 0x11, 0x00, 0xb6, 0x52, 0x02, 0x41, 0xba, 0x12, 0x43, 0x01,
+        0xad, 0x0d,
+    ];
+    #[rustfmt::skip]
+    let res = [
+        MinuteTimeData { price: 11.31, reversed: 0,  vol: 5302 },
+        MinuteTimeData { price: 11.33, reversed: -1, vol: 1210 },
+        MinuteTimeData { price: 11.3,  reversed: 1,  vol: 877 },
+    ];
+    minute.parse(arr).unwrap();
+    assert_eq!(res, minute.data.as_slice());
+}
+
+#[test]
+fn history_new_modify() {
+    let minute = HistoryMinuteTime::new(Market::SZ, "000001", 20210923);
+    let mut minute2 = HistoryMinuteTime::new(Market::SH, "600000", 20200101);
+    crate::tcp::tests::same_request(&minute2, &minute2.request());
+    minute2.market(Market::SZ).code("000001").date(20210923);
+    compare!(HistoryMinuteTime::default(), minute, minute2);
+}
+
+#[test]
+fn history_connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(HistoryMinuteTime::default())
+}
+
+#[test]
+fn history_parse() {
+    let mut minute = HistoryMinuteTime::default();
+    let arr = vec![
+        0x03, 0x00, 0x33, 0x33, 0x33, 0x41, 0x94, 0x1b, 0x00, 0x8e, 0xc0, 0x01, 0x41, 0x41, 0xb6,
+        0x2c, 0x05, 0x02, 0xaa, 0x33,
+    ];
+    #[rustfmt::skip]
+    let res = [
+        MinuteTimeData { price: 17.48, reversed: 0,  vol: 12302 },
+        MinuteTimeData { price: 17.47, reversed: -1, vol: 2870 },
+        MinuteTimeData { price: 17.52, reversed: 2,  vol: 3306 },
+    ];
+    minute.parse(arr).unwrap();
+    assert_eq!(res, minute.data.as_slice());
+}
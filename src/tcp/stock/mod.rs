@@ -1,5 +1,31 @@
 mod kline;
-pub use kline::{Kline, KlineData};
+pub use kline::{IndexKline, IndexKlineData, IndexKlineRequest, Kline, KlineData, KlineRequest};
 
 mod xdxr;
 pub use xdxr::*;
+
+mod quotes;
+pub use quotes::{SecurityQuotes, SecurityQuotesData, SecurityQuotesRequest};
+
+mod minute;
+pub use minute::{
+    HistoryMinuteTime, HistoryMinuteTimeRequest, MinuteTime, MinuteTimeData, MinuteTimeRequest,
+};
+
+mod transaction;
+pub use transaction::{
+    HistoryTransaction, HistoryTransactionRequest, Transaction, TransactionData, TransactionRequest,
+};
+
+mod company;
+pub use company::{
+    CompanyCategory, CompanyCategoryData, CompanyCategoryRequest, CompanyContent,
+    CompanyContentRequest,
+};
+
+mod finance;
+pub use finance::{FinanceInfo, FinanceInfoData, FinanceInfoRequest};
+mod file;
+pub use file::{FileChunk, FileChunkRequest, ReportFile};
+mod block;
+pub use block::{BlockData, BlockInfo, BlockMeta, BlockMetaData, BlockMetaRequest};
@@ -0,0 +1,305 @@
+use crate::tcp::{ensure_len, Market, Request, Result, Tdx};
+
+/// 查询实时行情快照（五档盘口）。对应于 pytdx 中的 hq.get_security_quotes、GetSecurityQuotesCmd。
+///
+/// ## 注意
+/// - 一次请求的证券数量不宜超过 80 个；
+/// - 响应的证券顺序与请求的顺序一致，但服务器可能略去无效的代码。
+#[derive(Debug, Clone)]
+pub struct SecurityQuotes<'d> {
+    pub send: Box<[u8]>,
+    /// (market, code) 的列表
+    pub codes: Vec<(Market, &'d str)>,
+    pub response: Vec<u8>,
+    pub data: Vec<SecurityQuotesData>,
+}
+
+/// 默认查询 #sz000001# 。
+impl<'d> Default for SecurityQuotes<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            codes: vec![(Market::SZ, "000001")],
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<'d> SecurityQuotes<'d> {
+    /// 参数为 (market, code) 的列表。
+    ///
+    /// ## panic
+    /// 当某个 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(codes: Vec<(Market, &'d str)>) -> Self {
+        Self {
+            send: Self::pack(&codes),
+            codes,
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// 修改查询的证券列表。
+    ///
+    /// ## panic
+    /// 当某个 code 的字节长度不是 6 时，程序会 panic。
+    pub fn codes(&mut self, codes: Vec<(Market, &'d str)>) -> &mut Self {
+        self.send = Self::pack(&codes);
+        self.codes = codes;
+        self
+    }
+
+    /// 前 22 字节为固定的头部（含请求体长度和证券数量），之后每个证券占 7 字节：
+    /// ```python
+    /// struct.pack("<B6s", market, code) # python 中的解读方式
+    /// ```
+    fn pack(codes: &[(Market, impl AsRef<str>)]) -> Box<[u8]> {
+        let mut send = Vec::with_capacity(22 + codes.len() * 7);
+        send.extend_from_slice(&Self::SEND[..22]);
+        let len = (codes.len() * 7 + 12) as u16;
+        send[6..8].copy_from_slice(&len.to_le_bytes());
+        send[8..10].copy_from_slice(&len.to_le_bytes());
+        send[20..22].copy_from_slice(&(codes.len() as u16).to_le_bytes());
+        for (market, code) in codes {
+            send.push(market.code() as u8);
+            send.extend_from_slice(&code.as_ref().as_bytes()[..6]);
+        }
+        send.into()
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> SecurityQuotesRequest {
+        SecurityQuotesRequest::new(self.codes.iter().map(|&(m, c)| (m, c.to_owned())).collect())
+    }
+}
+
+impl<'a> Tdx for SecurityQuotes<'a> {
+    type Item = [SecurityQuotesData];
+
+    /// #sz000001# 行情快照的请求字节。长度为 29。
+    /// ```python
+    /// struct.pack("<HIHHIIHH", 0x10c, 0x02006320, len, len, 0x5053e, 0, 0, count) # 头部
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x01, 0x20, 0x63, 0x00, 0x02, 0x13, 0x00, 0x13, 0x00, 0x3e, 0x05, 0x05, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31,
+    ];
+    const TAG: &'static str = "行情快照";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`SecurityQuotes`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityQuotesRequest {
+    /// (market, code) 的列表
+    pub codes: Vec<(Market, String)>,
+}
+
+impl SecurityQuotesRequest {
+    pub fn new(codes: Vec<(Market, String)>) -> Self {
+        Self { codes }
+    }
+}
+
+impl Request for SecurityQuotesRequest {
+    const TAG: &'static str = <SecurityQuotes as Tdx>::TAG;
+    type Response = Vec<SecurityQuotesData>;
+
+    /// ## panic
+    /// 当某个 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        SecurityQuotes::pack(&self.codes)
+    }
+
+    /// 跳过前 2 字节，之后 2 字节表示证券数量，剩余字节使用 [`SecurityQuotesData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        ensure_len(Self::TAG, v, 4)?;
+        let count = crate::bytes_helper::u16_from_le_bytes(v, 2) as usize;
+        let mut pos = 4;
+        let mut data = Vec::with_capacity(count);
+        for _ in 0..count {
+            ensure_len(Self::TAG, v, pos + SecurityQuotesData::MIN_LEN)?;
+            data.push(SecurityQuotesData::parse(v, &mut pos)?);
+        }
+        ensure_len(Self::TAG, v, pos)?;
+        Ok(data)
+    }
+}
+
+/// [`SecurityQuotes`] 的解析结果。价格单位为元，成交量单位为手。
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct SecurityQuotesData {
+    pub market: u8,
+    /// 6 位证券代码
+    pub code: String,
+    /// 现价
+    pub price: f64,
+    /// 昨收
+    pub preclose: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    /// 服务器时间（原始值，比如 `14253011` 表示 14:25:30.11）
+    pub servertime: i32,
+    /// 总量
+    pub vol: i32,
+    /// 现量
+    pub cur_vol: i32,
+    /// 成交额，单位：元
+    pub amount: f64,
+    /// 内盘
+    pub s_vol: i32,
+    /// 外盘
+    pub b_vol: i32,
+    /// 买一至买五的价格
+    pub bid: [f64; 5],
+    /// 卖一至卖五的价格
+    pub ask: [f64; 5],
+    /// 买一至买五的挂单量
+    pub bid_vol: [i32; 5],
+    /// 卖一至卖五的挂单量
+    pub ask_vol: [i32; 5],
+}
+
+impl SecurityQuotesData {
+    /// 一个证券的行情至少占的字节数：19 字节定长数据和 37 个至少 1 字节的 price 。
+    pub const MIN_LEN: usize = 19 + 37;
+
+    /// 从 `pos` 位置开始解析一个证券的行情，并把 `pos` 移动到下一个证券的起始位置。
+    ///
+    /// 除了开头 9 字节（`<B6sH`：市场、代码、未知）、成交额（4 字节）
+    /// 和末尾若干字节之外，其余数据都使用 [`price`][crate::tcp::helper::price] 解析。
+    /// 所有价格都是相对于现价的差值，且放大了 100 倍。
+    ///
+    /// 字节不足或者价格超出 i32 的范围时返回 [`Error::Parse`][crate::tcp::Error::Parse] 。
+    #[rustfmt::skip]
+    pub fn parse(v: &[u8], pos: &mut usize) -> Result<Self> {
+        use crate::{
+            bytes_helper::{u8_from_le_bytes, u32_from_le_bytes},
+            tcp::helper::{add_price, price, vol_amount},
+        };
+
+        let market = u8_from_le_bytes(v, *pos);
+        let code = String::from_utf8_lossy(&v[*pos + 1..*pos + 7]).into();
+        *pos += 9;
+
+        let base = price(v, pos)?;
+        let real = |p: i32| add_price(base, p).map(|p| p as f64 / 100.);
+        let mut quotes = Self { market, code, price: real(0)?,
+                                preclose:   real(price(v, pos)?)?,
+                                open:       real(price(v, pos)?)?,
+                                high:       real(price(v, pos)?)?,
+                                low:        real(price(v, pos)?)?,
+                                servertime: price(v, pos)?,
+                                ..Default::default() };
+        price(v, pos)?; // reversed_bytes1
+        quotes.vol = price(v, pos)?;
+        quotes.cur_vol = price(v, pos)?;
+        ensure_len("行情快照", v, *pos + 4)?;
+        quotes.amount = vol_amount(u32_from_le_bytes(v, *pos) as i32);
+        *pos += 4;
+        quotes.s_vol = price(v, pos)?;
+        quotes.b_vol = price(v, pos)?;
+        price(v, pos)?; // reversed_bytes2
+        price(v, pos)?; // reversed_bytes3
+
+        for i in 0..5 {
+            quotes.bid[i] = real(price(v, pos)?)?;
+            quotes.ask[i] = real(price(v, pos)?)?;
+            quotes.bid_vol[i] = price(v, pos)?;
+            quotes.ask_vol[i] = price(v, pos)?;
+        }
+
+        *pos += 2; // reversed_bytes4
+        for _ in 0..4 {
+            price(v, pos)?; // reversed_bytes5..8
+        }
+        *pos += 4; // reversed_bytes9 (涨速), active2
+        Ok(quotes)
+    }
+}
+
+#[test]
+fn new_modify() {
+    let quotes = SecurityQuotes::new(vec![(Market::SZ, "000001")]);
+    let mut quotes2 = SecurityQuotes::new(vec![(Market::SH, "600000"), (Market::SZ, "000002")]);
+    crate::tcp::tests::same_request(&quotes2, &quotes2.request());
+    quotes2.codes(vec![(Market::SZ, "000001")]);
+    compare!(SecurityQuotes::default(), quotes, quotes2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(SecurityQuotes::new(vec![
+        (Market::SZ, "000001"),
+        (Market::SH, "600000"),
+    ]))
+}
+
+#[test]
+fn parse() {
+    let mut quotes = SecurityQuotes::default();
+    let arr = vec![
+        0xb1, 0xcb, 0x01, 0x00, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31, 0x30, 0x0a, 0xab, 0x11,
+        0x4b, 0x46, 0x04, 0x4c, 0x93, 0xef, 0xcb, 0x0d, 0xeb, 0x11, 0x82, 0xc2, 0x63, 0x23, 0x59,
+        0xf7, 0x12, 0x4f, 0x87, 0xfd, 0x30, 0xbb, 0xc4, 0x32, 0x42, 0x00, 0x00, 0x01, 0x94, 0x19,
+        0xb3, 0x12, 0x41, 0x02, 0xb9, 0x33, 0xb1, 0x16, 0x42, 0x03, 0x95, 0x30, 0xa3, 0x22, 0x43,
+        0x04, 0x98, 0x2d, 0xbc, 0x1e, 0x44, 0x05, 0x90, 0x51, 0xb0, 0x0d, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x0c, 0x00, 0x30, 0x0a,
+    ];
+    let res = [SecurityQuotesData {
+        market: 0,
+        code: "000001".into(),
+        price: 11.31,
+        preclose: 11.2,
+        open: 11.25,
+        high: 11.35,
+        low: 11.19,
+        servertime: 14253011,
+        vol: 815234,
+        cur_vol: 35,
+        amount: 2465683712.0,
+        s_vol: 401223,
+        b_vol: 414011,
+        bid: [11.31, 11.3, 11.29, 11.28, 11.27],
+        ask: [11.32, 11.33, 11.34, 11.35, 11.36],
+        bid_vol: [1620, 3321, 3093, 2904, 5200],
+        ask_vol: [1203, 1457, 2211, 1980, 880],
+    }];
+    quotes.parse(arr).unwrap();
+    compare!(res, quotes.data.as_slice());
+}
+
+#[test]
+fn price_overflow() {
+    use crate::tcp::mock::encode_price;
+
+    // 现价为 i32::MAX ，昨收相对于现价的差值为 1
+    let mut v = vec![0, b'0', b'0', b'0', b'0', b'0', b'1', 0, 0];
+    encode_price(i32::MAX, &mut v);
+    encode_price(1, &mut v);
+    v.resize(v.len() + SecurityQuotesData::MIN_LEN, 0);
+    let err = SecurityQuotesData::parse(&v, &mut 0).unwrap_err();
+    assert!(
+        matches!(err, crate::tcp::Error::Parse { tag: "价格", .. }),
+        "{err}"
+    );
+}
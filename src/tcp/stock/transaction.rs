@@ -0,0 +1,440 @@
+use crate::tcp::{ensure_len, Market, Request, Result, Tdx};
+
+/// 查询当日分笔成交。对应于 pytdx 中的 hq.get_transaction_data、GetTransactionData。
+/// ## 注意
+/// 1. 只修改字段并不会更改待发送字节的内容。
+///    如果你需要修改查询条件，请使用相应的方法。
+/// 2. start 表示从最新一笔往前数的位置，每次最多返回 2000 笔。
+#[derive(Debug, Clone)]
+pub struct Transaction<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    pub start: u16,
+    pub count: u16,
+    pub response: Vec<u8>,
+    pub data: Vec<TransactionData>,
+}
+
+/// 默认查询 #sz000001# 最新的 3 笔成交。
+impl<'d> Default for Transaction<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            start: 0,
+            count: 3,
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<'d> Transaction<'d> {
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str, start: u16, count: u16) -> Self {
+        let mut tx = Self::default();
+        tx.market(market).code(code).start(start).count(count);
+        tx
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[12..14].copy_from_slice(&market.code().to_le_bytes());
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[14..20].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 修改起始位置。
+    pub fn start(&mut self, start: u16) -> &mut Self {
+        self.start = start;
+        self.send[20..22].copy_from_slice(&start.to_le_bytes());
+        self
+    }
+
+    /// 修改查询数量。
+    pub fn count(&mut self, count: u16) -> &mut Self {
+        self.count = count;
+        self.send[22..24].copy_from_slice(&count.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> TransactionRequest {
+        TransactionRequest::new(self.market, self.code, self.start, self.count)
+    }
+}
+
+impl<'a> Tdx for Transaction<'a> {
+    type Item = [TransactionData];
+
+    /// #sz000001# 最新 3 笔成交的请求字节。长度为 24。
+    /// ```python
+    /// struct.pack("<H6sHH", market, code, start, count) # 后 12 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x17, 0x08, 0x01, 0x01, 0x01, 0x0e, 0x00, 0x0e, 0x00, 0xc5, 0x0f, 0x00, 0x00, 0x30,
+        0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x00, 0x03, 0x00,
+    ];
+    const TAG: &'static str = "分笔成交";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`Transaction`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionRequest {
+    pub market: Market,
+    pub code: String,
+    pub start: u16,
+    pub count: u16,
+}
+
+impl TransactionRequest {
+    pub fn new(market: Market, code: impl Into<String>, start: u16, count: u16) -> Self {
+        Self {
+            market,
+            code: code.into(),
+            start,
+            count,
+        }
+    }
+}
+
+impl Request for TransactionRequest {
+    const TAG: &'static str = <Transaction as Tdx>::TAG;
+    type Response = Vec<TransactionData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; Transaction::LEN];
+        arr.copy_from_slice(Transaction::SEND);
+        arr[12..14].copy_from_slice(&self.market.code().to_le_bytes());
+        arr[14..20].copy_from_slice(self.code.as_bytes());
+        arr[20..22].copy_from_slice(&self.start.to_le_bytes());
+        arr[22..24].copy_from_slice(&self.count.to_le_bytes());
+        arr.into()
+    }
+
+    /// 前 2 字节表示数量，剩余字节使用 [`TransactionData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        TransactionData::check(Self::TAG, v, 2, true)?;
+        TransactionData::parse(v, 2, true)
+    }
+}
+
+/// 查询历史某日的分笔成交。
+/// 对应于 pytdx 中的 hq.get_history_transaction_data、GetHistoryTransactionData。
+/// ## 注意
+/// 1. 只修改字段并不会更改待发送字节的内容。
+///    如果你需要修改查询条件，请使用相应的方法。
+/// 2. start 表示从当日最后一笔往前数的位置，每次最多返回 2000 笔。
+#[derive(Debug, Clone)]
+pub struct HistoryTransaction<'d> {
+    pub send: Box<[u8]>,
+    pub market: Market,
+    pub code: &'d str,
+    /// `YYYYMMDD` 格式的日期，比如 `20210923`。
+    pub date: u32,
+    pub start: u16,
+    pub count: u16,
+    pub response: Vec<u8>,
+    pub data: Vec<TransactionData>,
+}
+
+/// 默认查询 #sz000001# 在 20210923 的最后 3 笔成交。
+impl<'d> Default for HistoryTransaction<'d> {
+    fn default() -> Self {
+        Self {
+            send: {
+                let mut arr = [0; Self::LEN];
+                arr.copy_from_slice(Self::SEND);
+                arr.into()
+            },
+            market: Market::SZ,
+            code: "000001",
+            date: 20210923,
+            start: 0,
+            count: 3,
+            response: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+}
+
+impl<'d> HistoryTransaction<'d> {
+    /// date 为 `YYYYMMDD` 格式的日期。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn new(market: Market, code: &'d str, date: u32, start: u16, count: u16) -> Self {
+        let mut tx = Self::default();
+        tx.market(market)
+            .code(code)
+            .date(date)
+            .start(start)
+            .count(count);
+        tx
+    }
+
+    /// 修改市场。
+    pub fn market(&mut self, market: Market) -> &mut Self {
+        self.market = market;
+        self.send[16..18].copy_from_slice(&market.code().to_le_bytes());
+        self
+    }
+
+    /// 修改股票。当代码不正确时，不能正常得到响应。
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    pub fn code(&mut self, code: &'d str) -> &mut Self {
+        self.code = code;
+        self.send[18..24].copy_from_slice(code.as_bytes());
+        self
+    }
+
+    /// 修改日期。
+    pub fn date(&mut self, date: u32) -> &mut Self {
+        self.date = date;
+        self.send[12..16].copy_from_slice(&date.to_le_bytes());
+        self
+    }
+
+    /// 修改起始位置。
+    pub fn start(&mut self, start: u16) -> &mut Self {
+        self.start = start;
+        self.send[24..26].copy_from_slice(&start.to_le_bytes());
+        self
+    }
+
+    /// 修改查询数量。
+    pub fn count(&mut self, count: u16) -> &mut Self {
+        self.count = count;
+        self.send[26..28].copy_from_slice(&count.to_le_bytes());
+        self
+    }
+
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> HistoryTransactionRequest {
+        HistoryTransactionRequest::new(self.market, self.code, self.date, self.start, self.count)
+    }
+}
+
+impl<'a> Tdx for HistoryTransaction<'a> {
+    type Item = [TransactionData];
+
+    /// #sz000001# 在 20210923 的最后 3 笔成交的请求字节。长度为 28。
+    /// ```python
+    /// struct.pack("<IH6sHH", date, market, code, start, count) # 后 16 字节
+    /// ```
+    const SEND: &'static [u8] = &[
+        0x0c, 0x01, 0x30, 0x01, 0x00, 0x01, 0x12, 0x00, 0x12, 0x00, 0xb5, 0x0f, 0xeb, 0x64, 0x34,
+        0x01, 0x00, 0x00, 0x30, 0x30, 0x30, 0x30, 0x30, 0x31, 0x00, 0x00, 0x03, 0x00,
+    ];
+    const TAG: &'static str = "历史分笔成交";
+
+    fn send(&self) -> &[u8] {
+        &self.send
+    }
+
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.response = v;
+        Ok(())
+    }
+
+    fn result(&self) -> &Self::Item {
+        &self.data
+    }
+}
+
+/// [`HistoryTransaction`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryTransactionRequest {
+    pub market: Market,
+    pub code: String,
+    pub date: u32,
+    pub start: u16,
+    pub count: u16,
+}
+
+impl HistoryTransactionRequest {
+    /// date 为 `YYYYMMDD` 格式的日期。
+    pub fn new(market: Market, code: impl Into<String>, date: u32, start: u16, count: u16) -> Self {
+        Self {
+            market,
+            code: code.into(),
+            date,
+            start,
+            count,
+        }
+    }
+}
+
+impl Request for HistoryTransactionRequest {
+    const TAG: &'static str = <HistoryTransaction as Tdx>::TAG;
+    type Response = Vec<TransactionData>;
+
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; HistoryTransaction::LEN];
+        arr.copy_from_slice(HistoryTransaction::SEND);
+        arr[12..16].copy_from_slice(&self.date.to_le_bytes());
+        arr[16..18].copy_from_slice(&self.market.code().to_le_bytes());
+        arr[18..24].copy_from_slice(self.code.as_bytes());
+        arr[24..26].copy_from_slice(&self.start.to_le_bytes());
+        arr[26..28].copy_from_slice(&self.count.to_le_bytes());
+        arr.into()
+    }
+
+    /// 前 2 字节表示数量，跳过 4 个未知字节之后使用 [`TransactionData::parse`] 解析。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        TransactionData::check(Self::TAG, v, 6, false)?;
+        TransactionData::parse(v, 6, false)
+    }
+}
+
+/// 一笔成交。价格单位为元，成交量单位为手。
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct TransactionData {
+    pub hour: u16,
+    pub minute: u16,
+    pub price: f64,
+    pub vol: i32,
+    /// 成交笔数。历史分笔成交没有该值。
+    pub num: Option<i32>,
+    /// 0 表示买；1 表示卖；2 表示中性（比如集合竞价）。
+    pub buyorsell: i32,
+}
+
+impl TransactionData {
+    /// 检查有效数据的长度：前 2 字节表示数量，从 `pos` 开始每笔至少 6 字节（`with_num` 为 true
+    /// 时至少 7 字节）。
+    pub fn check(tag: &'static str, v: &[u8], pos: usize, with_num: bool) -> Result<()> {
+        ensure_len(tag, v, 2)?;
+        let count = crate::bytes_helper::u16_from_le_bytes(v, 0) as usize;
+        ensure_len(tag, v, pos + count * (6 + with_num as usize))
+    }
+
+    /// 解析分笔成交。`v` 的前 2 字节表示数量，`pos` 为第一笔的起始位置。
+    ///
+    /// 每笔成交以 2 字节的分钟数开头，之后的数据都使用 [`price`][crate::tcp::helper::price]
+    /// 解析：价格（相对于上一笔的差值，放大了 100 倍）、成交量、成交笔数（`with_num` 为 true
+    /// 时才有）、买卖方向和一个未知值。
+    ///
+    /// 字节不足时返回 [`Error::Parse`][crate::tcp::Error::Parse] 。
+    pub fn parse(v: &[u8], mut pos: usize, with_num: bool) -> Result<Vec<Self>> {
+        use crate::{
+            bytes_helper::u16_from_le_bytes,
+            tcp::helper::{add_price, price},
+        };
+
+        let count = u16_from_le_bytes(v, 0) as usize;
+        let mut base = 0;
+        (0..count)
+            .map(|_| {
+                ensure_len("分笔成交", v, pos + 2)?;
+                let minutes = u16_from_le_bytes(v, pos);
+                pos += 2;
+                base = add_price(base, price(v, &mut pos)?)?;
+                let tx = Self {
+                    hour: minutes / 60,
+                    minute: minutes % 60,
+                    price: base as f64 / 100.,
+                    vol: price(v, &mut pos)?,
+                    num: with_num.then(|| price(v, &mut pos)).transpose()?,
+                    buyorsell: price(v, &mut pos)?,
+                };
+                price(v, &mut pos)?;
+                Ok(tx)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn new_modify() {
+    let tx = Transaction::new(Market::SZ, "000001", 0, 3);
+    let mut tx2 = Transaction::new(Market::SH, "600000", 100, 2000);
+    crate::tcp::tests::same_request(&tx2, &tx2.request());
+    tx2.market(Market::SZ).code("000001").start(0).count(3);
+    compare!(Transaction::default(), tx, tx2);
+
+    let tx = HistoryTransaction::new(Market::SZ, "000001", 20210923, 0, 3);
+    let mut tx2 = HistoryTransaction::new(Market::SH, "600000", 20200101, 100, 2000);
+    crate::tcp::tests::same_request(&tx2, &tx2.request());
+    tx2.market(Market::SZ)
+        .code("000001")
+        .date(20210923)
+        .start(0)
+        .count(3);
+    compare!(HistoryTransaction::default(), tx, tx2);
+}
+
+#[test]
+fn connection() -> crate::tcp::Result<()> {
+    crate::tcp::tests::connection(Transaction::default())?;
+    crate::tcp::tests::connection(HistoryTransaction::default())
+}
+
+#[test]
+fn parse() {
+    let mut tx = Transaction::default();
+    let arr = vec![
+        0x03, 0x00, 0x3a, 0x02, 0xab, 0x11, 0xb0, 0x17, 0x23, 0x00, 0x00, 0x3a, 0x02, 0x01, 0x14,
+        0x02, 0x01, 0x00, 0x3b, 0x02, 0x42, 0x84, 0x06, 0x0b, 0x00, 0x00,
+    ];
+    #[rustfmt::skip]
+    let res = [
+        TransactionData { hour: 9, minute: 30, price: 11.31, vol: 1520, num: Some(35), buyorsell: 0 },
+        TransactionData { hour: 9, minute: 30, price: 11.32, vol: 20,   num: Some(2),  buyorsell: 1 },
+        TransactionData { hour: 9, minute: 31, price: 11.3,  vol: 388,  num: Some(11), buyorsell: 0 },
+    ];
+    tx.parse(arr).unwrap();
+    assert_eq!(res, tx.data.as_slice());
+
+    let mut tx = HistoryTransaction::default();
+    let arr = vec![
+        0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3a, 0x02, 0x94, 0x1b, 0x82, 0x2f, 0x02, 0x00, 0x3a,
+        0x02, 0x41, 0x0c, 0x01, 0x00, 0x3b, 0x02, 0x02, 0x96, 0x06, 0x00, 0x00,
+    ];
+    #[rustfmt::skip]
+    let res = [
+        TransactionData { hour: 9, minute: 30, price: 17.48, vol: 3010, num: None, buyorsell: 2 },
+        TransactionData { hour: 9, minute: 30, price: 17.47, vol: 12,   num: None, buyorsell: 1 },
+        TransactionData { hour: 9, minute: 31, price: 17.49, vol: 406,  num: None, buyorsell: 0 },
+    ];
+    tx.parse(arr).unwrap();
+    assert_eq!(res, tx.data.as_slice());
+}
@@ -1,10 +1,10 @@
-use crate::tcp::Tdx;
+use crate::tcp::{ensure_len, KlineCategory, Market, Request, Result, Tdx};
 
 /// 对应 pytdx 的 hq.get_xdxr_info、GetXdXrInfo。获取单只股票的股本变迁信息。
 #[derive(Debug, Clone)]
 pub struct Xdxr<'d> {
     pub send: Box<[u8]>,
-    pub market: u16,
+    pub market: Market,
     pub code: &'d str,
     pub response: Vec<u8>,
     pub count: Option<usize>,
@@ -19,7 +19,7 @@ impl<'d> Default for Xdxr<'d> {
                 arr.copy_from_slice(Self::SEND);
                 arr.into()
             },
-            market: 0,
+            market: Market::SZ,
             code: "000001",
             response: Vec::new(),
             count: None,
@@ -28,6 +28,13 @@ impl<'d> Default for Xdxr<'d> {
     }
 }
 
+impl Xdxr<'_> {
+    /// 对应的请求，见 [`Request`] 。
+    pub fn request(&self) -> XdxrRequest {
+        XdxrRequest::new(self.market, self.code)
+    }
+}
+
 impl<'a> Tdx for Xdxr<'a> {
     type Item = [XdxrData];
 
@@ -38,32 +45,16 @@ impl<'a> Tdx for Xdxr<'a> {
     ];
     const TAG: &'static str = "除权除息";
 
-    fn send(&mut self) -> &[u8] {
+    fn send(&self) -> &[u8] {
         &self.send
     }
 
-    fn parse(&mut self, v: Vec<u8>) {
-        if v.len() < 11 {
-            return;
-        }
-        let count = crate::bytes_helper::u16_from_le_bytes(&v, 9) as usize;
-        let old = self.count.replace(count);
-        if old.is_some() {
-            self.data.resize_with(count, XdxrData::default);
-            self.data
-                .iter_mut()
-                .zip(v[11..].chunks_exact(29).map(XdxrData::parse))
-                .map(|(d, x)| *d = x)
-                .last();
-        } else {
-            self.data = Vec::with_capacity(count);
-            v[11..]
-                .chunks_exact(29)
-                .map(XdxrData::parse)
-                .map(|x| self.data.push(x))
-                .last();
-        };
+    /// 没有除权除息记录时，响应的字节可能不足 11 字节，此时 count 为 None 。
+    fn parse(&mut self, v: Vec<u8>) -> Result<()> {
+        self.data = self.request().decode(&v)?;
+        self.count = (v.len() >= 11).then_some(self.data.len());
         self.response = v;
+        Ok(())
     }
 
     fn result(&self) -> &Self::Item {
@@ -71,7 +62,58 @@ impl<'a> Tdx for Xdxr<'a> {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+/// [`Xdxr`] 的请求参数，见 [`Request`] 。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XdxrRequest {
+    pub market: Market,
+    pub code: String,
+}
+
+impl XdxrRequest {
+    pub fn new(market: Market, code: impl Into<String>) -> Self {
+        Self {
+            market,
+            code: code.into(),
+        }
+    }
+}
+
+impl Request for XdxrRequest {
+    const TAG: &'static str = <Xdxr as Tdx>::TAG;
+    type Response = Vec<XdxrData>;
+
+    /// ```python
+    /// struct.pack("<B6s", market, code) # 后 7 字节
+    /// ```
+    ///
+    /// ## panic
+    /// 当 code 的字节长度不是 6 时，程序会 panic。
+    fn send(&self) -> Box<[u8]> {
+        let mut arr = [0; Xdxr::LEN];
+        arr.copy_from_slice(Xdxr::SEND);
+        arr[14] = self.market.code() as u8;
+        arr[15..21].copy_from_slice(&self.code.as_bytes()[..6]);
+        arr.into()
+    }
+
+    /// 跳过前 9 字节，之后 2 字节表示记录数量，剩余字节中，每 29 字节使用 [`XdxrData::parse`] 解析。
+    ///
+    /// 没有除权除息记录时，响应的字节可能不足 11 字节，此时得到空的列表。
+    fn decode(&self, v: &[u8]) -> Result<Self::Response> {
+        if v.len() < 11 {
+            return Ok(Vec::new());
+        }
+        let count = crate::bytes_helper::u16_from_le_bytes(v, 9) as usize;
+        ensure_len(Self::TAG, v, 11 + count * 29)?;
+        Ok(v[11..]
+            .chunks_exact(29)
+            .take(count)
+            .map(XdxrData::parse)
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
 pub struct XdxrData {
     pub market: u8,
     /// 6 位股票代码
@@ -122,8 +164,8 @@ impl XdxrData {
             }
         }
         let market = u8_from_le_bytes(bytes, 0);
-        let code = unsafe { std::str::from_utf8_unchecked(&bytes[1..7]) }.into();
-        let date = crate::tcp::helper::datetime(&bytes[8..12], 9).to_u32();
+        let code = String::from_utf8_lossy(&bytes[1..7]).into();
+        let date = crate::tcp::helper::datetime(&bytes[8..12], KlineCategory::Day).to_u32();
         let category = u8_from_le_bytes(bytes, 12);
         let (fh_qltp, pgj_qzgb, sg_hltp, pg_hzgb) = match category {
             1 | 11..=14 => (
@@ -153,10 +195,18 @@ impl XdxrData {
 }
 
 #[test]
-fn connection() -> std::io::Result<()> {
+fn connection() -> crate::tcp::Result<()> {
     crate::tcp::tests::connection(Xdxr::default())
 }
 
+#[test]
+fn request() {
+    let xdxr = Xdxr::default();
+    crate::tcp::tests::same_request(&xdxr, &xdxr.request());
+    let send = XdxrRequest::new(Market::SH, "600000").send();
+    assert_eq!(&send[14..], &[1, b'6', b'0', b'0', b'0', b'0', b'0']);
+}
+
 #[test]
 fn xdxrdata_parse() {
     let target = XdxrData {
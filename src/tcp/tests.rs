@@ -11,7 +11,7 @@ macro_rules! compare {
     };
 }
 
-use super::{Result, Tcp, Tdx};
+use super::{Request, Result, Tcp, Tdx};
 
 pub fn connection<T: Tdx>(mut tdx: T) -> Result<()>
 where
@@ -32,3 +32,19 @@ where
     println!("recv: {res:?}");
     Ok(())
 }
+
+/// 与 [`connection`] 相同，但连接的是扩展行情服务器。
+pub fn exhq_connection<T: Tdx>(mut tdx: T) -> Result<()>
+where
+    <T as Tdx>::Item: std::fmt::Debug,
+{
+    println!("send: {:?}", tdx.send());
+    println!("recv: {:?}", tdx.recv_parsed(&mut Tcp::new_exhq()?)?);
+    Ok(())
+}
+
+/// 检查 [`Tdx`] 结构体与其对应的 [`Request`] 的请求字节和 TAG 一致。
+pub fn same_request<T: Tdx, R: Request>(tdx: &T, req: &R) {
+    assert_eq!(tdx.send(), &*req.send(), "{}", T::TAG);
+    assert_eq!(T::TAG, R::TAG);
+}
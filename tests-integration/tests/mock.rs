@@ -0,0 +1,33 @@
+//! 使用本地的模拟服务器，离线测试 tcp 请求。
+use rustdx::tcp::{
+    mock::MockServer, stock::Kline, Market, Result, SecurityCount, SecurityList, Tdx,
+};
+
+#[test]
+fn security_count_and_list() -> Result<()> {
+    let server = MockServer::start()?;
+    let mut tcp = server.connect()?;
+    assert_eq!(
+        *SecurityCount::new(Market::SZ).recv_parsed(&mut tcp)?,
+        13471
+    );
+    let mut list = SecurityList::default();
+    assert_eq!(
+        list.recv_parsed(&mut tcp)?.len(),
+        rustdx::tcp::mock::SECURITIES.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn kline_pages() -> Result<()> {
+    let server = MockServer::start()?;
+    let mut tcp = server.connect()?;
+    let mut kline = Kline::default();
+    kline.count(2);
+    assert_eq!(kline.recv_parsed(&mut tcp)?.len(), 2);
+    let all = kline.recv_all(&mut tcp)?;
+    assert_eq!(all.len(), rustdx::tcp::mock::BARS.len());
+    assert_eq!(kline.count, 2);
+    Ok(())
+}
@@ -1,18 +1,17 @@
 // use insta::{assert_debug_snapshot, assert_yaml_snapshot};
 use insta::assert_debug_snapshot;
-use rustdx::tcp::{self, Tcp, Tdx};
-use std::io::Result;
+use rustdx::tcp::{self, Market, Result, Tcp, Tdx};
 
 #[test]
 fn tcp_security_count() -> Result<()> {
     let mut tcp = Tcp::new()?;
 
-    let mut count = tcp::SecurityCount::new(0); // sz
+    let mut count = tcp::SecurityCount::new(Market::SZ); // sz
     let c = *count.recv_parsed(&mut tcp)?;
     assert_debug_snapshot!("security-count-sz", count);
     assert_debug_snapshot!(c, @"13471");
 
-    let mut count = tcp::SecurityCount::new(1); // sh
+    let mut count = tcp::SecurityCount::new(Market::SH); // sh
     let c = *count.recv_parsed(&mut tcp)?;
     assert_debug_snapshot!("security-count-sh", count);
     assert_debug_snapshot!(c, @"18065");